@@ -0,0 +1,58 @@
+use bevy::prelude::*;
+use bevy_btml::btml;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_systems(Startup, setup)
+        .run();
+}
+
+// Without a spawner, btml! returns a bundle that still contains its children
+fn label(text: &str, color: Color) -> impl Bundle {
+    btml!(
+        let text = text.to_string();
+        <Node padding=UiRect::all(Val::Px(10.0))>
+            <BackgroundColor>color</BackgroundColor>
+            <children>
+                <Text>text</Text>
+                <TextFont font_size=20.0 />
+            </children>
+        </Node>
+    )
+}
+
+// Control flow is spawned with the bundle, the values it uses are moved into it
+fn inventory(items: Vec<&'static str>) -> impl Bundle {
+    btml!(
+        <Node flex_direction=FlexDirection::Column>
+            for item in items {
+                <Text>item.to_string()</Text>
+            } else {
+                <Text>"Empty inventory".to_string()</Text>
+            }
+        </Node>
+    )
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn(Camera2d);
+
+    commands.spawn((
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            row_gap: Val::Px(10.0),
+            ..default()
+        },
+        children![
+            label("Red", Color::srgb(0.6, 0.1, 0.1)),
+            label("Green", Color::srgb(0.1, 0.6, 0.1)),
+            inventory(vec!["Sword", "Shield"]),
+            inventory(Vec::new()),
+        ],
+    ));
+}
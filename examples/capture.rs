@@ -0,0 +1,71 @@
+use bevy::prelude::*;
+use bevy_btml::btml;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_systems(Startup, setup)
+        .add_systems(Update, update_menu)
+        .run();
+}
+
+#[derive(Resource)]
+struct Menu {
+    root: Entity,
+    buttons: Vec<Entity>,
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn(Camera2d);
+
+    let labels = ["Play", "Options", "Quit"];
+
+    btml!(commands,
+        <Node
+            #root,
+            width=Val::Percent(100.0),
+            height=Val::Percent(100.0),
+            flex_direction=FlexDirection::Column,
+            justify_content=JustifyContent::Center,
+            align_items=AlignItems::Center,
+            row_gap=Val::Px(10.0)
+        >
+            <children>
+                // Inside a loop every spawned id is collected into a Vec<Entity>
+                for label in labels {
+                    <Button #buttons />
+                    <Node padding=UiRect::all(Val::Px(10.0)) />
+                    <BackgroundColor>Color::srgb(0.2, 0.2, 0.2)</BackgroundColor>
+                    <children>
+                        <Text>label.to_string()</Text>
+                    </children>
+                }
+            </children>
+        </Node>
+    );
+
+    commands.insert_resource(Menu { root, buttons });
+}
+
+// The captured ids reach the menu without marker components
+fn update_menu(
+    menu: Res<Menu>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut visibility: Query<&mut Visibility>,
+    mut buttons: Query<(&Interaction, &mut BackgroundColor)>,
+) {
+    if keys.just_pressed(KeyCode::Escape)
+        && let Ok(mut visibility) = visibility.get_mut(menu.root)
+    {
+        visibility.toggle_visible_hidden();
+    }
+
+    for &button in &menu.buttons {
+        if let Ok((interaction, mut color)) = buttons.get_mut(button) {
+            color.0 = match interaction {
+                Interaction::None => Color::srgb(0.2, 0.2, 0.2),
+                _ => Color::srgb(0.3, 0.3, 0.5),
+            };
+        }
+    }
+}
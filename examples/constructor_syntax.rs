@@ -11,12 +11,15 @@ fn main() {
 fn setup(mut commands: Commands) {
     commands.spawn(Camera2d::default());
     btml!(commands,
-        <Node position_type=PositionType::Absolute, top=Val::Percent(50.), left=Val::Percent(50.)/>
+        // Top-level nodes are separate entities, so group the components with <entity>
+        <entity>
+            <Node position_type=PositionType::Absolute, top=Val::Percent(50.), left=Val::Percent(50.)/>
 
-        // Single-arg
-        <Text(new)>"Child Text"</Text>
+            // Single-arg
+            <Text(new)>"Child Text"</Text>
 
-        // Multi-arg
-        <UiTransform rotation=Rot2::from_sin_cos(1., 0.), translation=Val2::percent(-50., -50.) />
+            // Multi-arg
+            <UiTransform rotation=Rot2::from_sin_cos(1., 0.), translation=Val2::percent(-50., -50.) />
+        </entity>
     );
 }
@@ -9,13 +9,9 @@ fn main() {
     App::new()
         .add_plugins(DefaultPlugins)
         .add_systems(Startup, setup)
-        .add_systems(Update, button_system)
         .run();
 }
 
-#[derive(Component)]
-struct ClickMeButton;
-
 #[derive(Component)]
 struct Counter {
     counter: i32,
@@ -30,11 +26,17 @@ fn setup(mut commands: Commands) {
             <children>
                 <Text>"Hello Bevy!".to_string()</Text>
                 <TextFont font_size=30.0 />
+                <TextLayout default .with_justify=Justify::Center />
                 <TextColor>Color::WHITE</TextColor>
             </children>
             <children>
-                <Button />
-                <ClickMeButton />
+                // Observers are added to the spawned button, and see the events of its text too
+                <Button
+                    on:over=|over: On<Pointer<Over>>, mut colors: Query<&mut BackgroundColor>| set_color(over.entity, DARK_GRAY, &mut colors),
+                    on:out=|out: On<Pointer<Out>>, mut colors: Query<&mut BackgroundColor>| set_color(out.entity, Color::BLACK, &mut colors),
+                    on:press=|press: On<Pointer<Press>>, mut colors: Query<&mut BackgroundColor>| set_color(press.entity, GRAY, &mut colors),
+                    on:click=on_click
+                />
                 <BackgroundColor>Color::BLACK</BackgroundColor>
                 <children>
                     <Text>"Click Me!".to_string()</Text>
@@ -53,24 +55,18 @@ fn setup(mut commands: Commands) {
     );
 }
 
-fn button_system(
-    mut interaction: Single<
-        (&Interaction, &mut BackgroundColor),
-        (Changed<Interaction>, With<ClickMeButton>),
-    >,
+fn set_color(entity: Entity, color: Color, colors: &mut Query<&mut BackgroundColor>) {
+    if let Ok(mut background) = colors.get_mut(entity) {
+        background.0 = color;
+    }
+}
+
+fn on_click(
+    click: On<Pointer<Click>>,
+    mut colors: Query<&mut BackgroundColor>,
     mut counter_query: Single<(&mut Text, &mut Counter)>,
 ) {
-    match interaction.0 {
-        Interaction::Pressed => {
-            counter_query.1.counter += 1;
-            *counter_query.0 = format!("Counter: {}", counter_query.1.counter).into();
-            interaction.1.0 = GRAY;
-        }
-        Interaction::Hovered => {
-            interaction.1.0 = DARK_GRAY;
-        }
-        Interaction::None => {
-            interaction.1.0 = Color::BLACK;
-        }
-    }
+    counter_query.1.counter += 1;
+    *counter_query.0 = format!("Counter: {}", counter_query.1.counter).into();
+    set_color(click.entity, DARK_GRAY, &mut colors);
 }
@@ -12,6 +12,13 @@ fn setup(mut commands: Commands) {
     commands.spawn(Camera2d);
 
     let items = vec!["Item 1", "Item 2", "Item 3"];
+    let friends: Vec<&str> = Vec::new();
+    // Shared by every row, which only sets what differs
+    let row = Node {
+        padding: UiRect::all(Val::Px(10.0)),
+        border: UiRect::all(Val::Px(2.0)),
+        ..default()
+    };
 
     btml!(commands,
         <Node
@@ -29,12 +36,10 @@ fn setup(mut commands: Commands) {
 
                 //All element will be seperated children of the parent since it can't have twice the same parent
                 for item in items.iter() {
-                    <Node
-                        padding=UiRect::all(Val::Px(10.0)),
-                        border=UiRect::all(Val::Px(2.0))
-                    >
+                    <Node ..row, min_width=Val::Px(200.0)>
                         <BorderColor(all)>Color::WHITE</BorderColor>
-                        <BackgroundColor>Color::srgb(0.2, 0.2, 0.2)</BackgroundColor>
+                        // Alternate the row color with the loop index
+                        <BackgroundColor>Color::srgb(0.2, 0.2, 0.2 + 0.1 * ($index % 2) as f32)</BackgroundColor>
                         <children>
                             <Text>format!("- {}", item)</Text>
                             <TextFont font_size=20.0 />
@@ -42,6 +47,14 @@ fn setup(mut commands: Commands) {
                         </children>
                     </Node>
                 }
+
+                // The else markup is only spawned when the loop doesn't yield anything
+                for friend in friends.iter() {
+                    <Text>friend.to_string()</Text>
+                } else {
+                    <Text>"No friends online".to_string()</Text>
+                    <TextColor>Color::srgb(0.5, 0.5, 0.5)</TextColor>
+                }
             </children>
         </Node>
     );
@@ -0,0 +1,56 @@
+use bevy::prelude::*;
+use bevy_btml::btml;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_systems(Startup, setup)
+        .run();
+}
+
+struct Item {
+    name: String,
+    count: u32,
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn(Camera2d);
+
+    let selected_item = Some(Item {
+        name: "Coconut".to_string(),
+        count: 3,
+    });
+    let current_quest: Option<&str> = None;
+
+    btml!(commands,
+        <Node
+            width=Val::Percent(100.0),
+            height=Val::Percent(100.0),
+            flex_direction=FlexDirection::Column,
+            justify_content=JustifyContent::Center,
+            align_items=AlignItems::Center
+        >
+            <children>
+                if let Some(Item { name, count }) = &selected_item && *count > 1 {
+                    <Text>format!("{} x{}", name, count)</Text>
+                    <TextFont font_size=30.0 />
+                } else if let Some(item) = &selected_item {
+                    <Text>item.name.clone()</Text>
+                    <TextFont font_size=30.0 />
+                } else {
+                    <Text>"Nothing selected".to_string()</Text>
+                    <TextFont font_size=30.0 />
+                }
+            </children>
+            <children>
+                // Only the else markup is spawned when there is no quest
+                let Some(quest) = current_quest else {
+                    <Text>"No active quest".to_string()</Text>
+                    <TextColor>Color::srgb(0.5, 0.5, 0.5)</TextColor>
+                };
+                <Text>quest.to_string()</Text>
+                <TextColor>Color::WHITE</TextColor>
+            </children>
+        </Node>
+    );
+}
@@ -0,0 +1,50 @@
+use bevy::prelude::*;
+use bevy_btml::{btml, btml_insert};
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_systems(Startup, setup)
+        .add_systems(Update, highlight)
+        .run();
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn(Camera2d);
+
+    btml!(commands,
+        <Node
+            width=Val::Percent(100.0),
+            height=Val::Percent(100.0),
+            justify_content=JustifyContent::Center,
+            align_items=AlignItems::Center
+        >
+            <children>
+                <Button />
+                <Node padding=UiRect::all(Val::Px(10.0)) />
+                <BackgroundColor>Color::srgb(0.2, 0.2, 0.2)</BackgroundColor>
+                <children>
+                    <Text>"Hover me".to_string()</Text>
+                </children>
+            </children>
+        </Node>
+    );
+}
+
+fn highlight(mut commands: Commands, buttons: Query<(Entity, &Interaction), Changed<Interaction>>) {
+    for (button, interaction) in &buttons {
+        let hovered = *interaction == Interaction::Hovered;
+        let color = if hovered {
+            Color::srgb(0.3, 0.3, 0.6)
+        } else {
+            Color::srgb(0.2, 0.2, 0.2)
+        };
+        let padding = if hovered { 12.0 } else { 10.0 };
+
+        // Components replace the ones already on the button, `..Default::default()` included
+        btml_insert!(commands.entity(button),
+            <BackgroundColor>color</BackgroundColor>
+            <Node padding=UiRect::all(Val::Px(padding)) />
+        );
+    }
+}
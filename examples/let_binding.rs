@@ -0,0 +1,45 @@
+use bevy::prelude::*;
+use bevy_btml::btml;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_systems(Startup, setup)
+        .run();
+}
+
+struct Stats {
+    hp: u32,
+    max_hp: u32,
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn(Camera2d);
+
+    let stats = Stats {
+        hp: 42,
+        max_hp: 100,
+    };
+
+    btml!(commands,
+        <Node
+            width=Val::Percent(100.0),
+            height=Val::Percent(100.0),
+            flex_direction=FlexDirection::Column,
+            justify_content=JustifyContent::Center,
+            align_items=AlignItems::Center
+        >
+            <children>
+                let label = format!("{} HP", stats.hp);
+                let ratio: f32 = stats.hp as f32 / stats.max_hp as f32;
+                <Text>label</Text>
+                <TextFont font_size=30.0 />
+                <children>
+                    <Node width=Val::Px(200.0 * ratio), height=Val::Px(20.0)>
+                        <BackgroundColor>Color::srgb(1.0 - ratio, ratio, 0.0)</BackgroundColor>
+                    </Node>
+                </children>
+            </children>
+        </Node>
+    );
+}
@@ -0,0 +1,54 @@
+use bevy::prelude::*;
+use bevy_btml::btml;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_systems(Startup, setup)
+        .run();
+}
+
+#[allow(dead_code)]
+enum MenuState {
+    Main,
+    Options { volume: u32 },
+    Credits,
+    Hidden,
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn(Camera2d);
+
+    let state = MenuState::Options { volume: 0 };
+
+    btml!(commands,
+        <Node
+            width=Val::Percent(100.0),
+            height=Val::Percent(100.0),
+            flex_direction=FlexDirection::Column,
+            justify_content=JustifyContent::Center,
+            align_items=AlignItems::Center
+        >
+            <children>
+                match state {
+                    MenuState::Main | MenuState::Credits => {
+                        <Text>"Main Menu".to_string()</Text>
+                        <TextFont font_size=30.0 />
+                    }
+                    // Guards are checked before the next arm
+                    MenuState::Options { volume } if volume > 0 => {
+                        <Text>format!("Volume: {}", volume)</Text>
+                        <TextFont font_size=30.0 />
+                    }
+                    MenuState::Options { .. } => {
+                        <Text>"Muted".to_string()</Text>
+                        <TextFont font_size=30.0 />
+                        <TextColor>Color::srgb(1.0, 0.0, 0.0)</TextColor>
+                    }
+                    // Empty arms spawn nothing
+                    MenuState::Hidden => {}
+                }
+            </children>
+        </Node>
+    );
+}
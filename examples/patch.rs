@@ -0,0 +1,68 @@
+use bevy::prelude::*;
+use bevy_btml::{btml, btml_patch};
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .insert_resource(Score {
+            points: 0,
+            players: vec![(1, "Alice"), (2, "Bob")],
+        })
+        .add_systems(Startup, setup)
+        .add_systems(Update, (play, update_hud).chain())
+        .run();
+}
+
+#[derive(Resource)]
+struct Score {
+    points: u32,
+    players: Vec<(u32, &'static str)>,
+}
+
+#[derive(Resource)]
+struct Hud(Entity);
+
+fn setup(mut commands: Commands) {
+    commands.spawn(Camera2d);
+
+    let hud = btml!(commands,
+        <Node flex_direction=FlexDirection::Column, row_gap=Val::Px(5.0) />
+    );
+    commands.insert_resource(Hud(hud));
+}
+
+fn play(time: Res<Time>, mut score: ResMut<Score>) {
+    let points = time.elapsed_secs() as u32;
+    if points != score.points {
+        score.points = points;
+        // Players take turns, the keys move their rows instead of patching them in place
+        if points.is_multiple_of(5) {
+            score.players.rotate_left(1);
+        }
+    }
+}
+
+fn update_hud(mut commands: Commands, hud: Res<Hud>, score: Res<Score>) {
+    if !score.is_changed() {
+        return;
+    }
+    let points = score.points;
+    let players = score.players.clone();
+
+    // Only the components whose value changed are inserted again
+    btml_patch!(commands, hud.0,
+        <Node flex_direction=FlexDirection::Column, row_gap=Val::Px(5.0) />
+        <children>
+            <Text>format!("Score: {}", points)</Text>
+        </children>
+        <children>
+            for (id, name) in players {
+                let color = if $first { Color::srgb(1.0, 0.8, 0.2) } else { Color::WHITE };
+                <entity key=id>
+                    <Text>name.to_string()</Text>
+                    <TextColor>color</TextColor>
+                </entity>
+            }
+        </children>
+    );
+}
@@ -0,0 +1,48 @@
+use bevy::prelude::*;
+use bevy_btml::btml;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_systems(Startup, setup)
+        .run();
+}
+
+// An existing spawn helper that doesn't use btml
+fn spawn_stat(parent: &mut ChildSpawnerCommands, label: &str, value: u32) {
+    parent.spawn((
+        Text::new(format!("{}: {}", label, value)),
+        TextFont::from_font_size(20.0),
+    ));
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn(Camera2d);
+
+    let stats = [("Strength", 12), ("Agility", 8), ("Wisdom", 15)];
+
+    btml!(commands,
+        <Node
+            width=Val::Percent(100.0),
+            height=Val::Percent(100.0),
+            flex_direction=FlexDirection::Column,
+            justify_content=JustifyContent::Center,
+            align_items=AlignItems::Center
+        >
+            <children>
+                <Text>"Stats".to_string()</Text>
+                <TextFont font_size=30.0 />
+
+                // The current spawner is available as `parent`
+                for (label, value) in stats {
+                    @{ spawn_stat(parent, label, value); }
+                }
+
+                // Or under another name
+                @{ |spawner|
+                    spawner.spawn(Text::new("Level up soon!"));
+                }
+            </children>
+        </Node>
+    );
+}
@@ -0,0 +1,56 @@
+use bevy::prelude::*;
+use bevy_btml::{BtmlSpawner, btml, btml_children, btml_template};
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_systems(Startup, (spawn_camera, setup).chain())
+        .run();
+}
+
+// Works with any spawner, from `Commands` to an exclusive `World`
+#[btml_template]
+fn label(parent: &mut impl BtmlSpawner, text: &str) {
+    btml!(parent,
+        <entity>
+            <Text(new)>text</Text>
+            <TextFont font_size=24.0 />
+            <TextColor>Color::WHITE</TextColor>
+        </entity>
+    );
+}
+
+// `=>` spawns directly into the world, so the camera exists as soon as the macro returns
+fn spawn_camera(world: &mut World) {
+    let camera = btml!(world => <Camera2d />);
+    assert!(world.entity(camera).contains::<Camera2d>());
+}
+
+fn setup(mut commands: Commands) {
+    let hud = btml!(commands,
+        <Node
+            width=Val::Percent(100.0),
+            height=Val::Percent(100.0),
+            flex_direction=FlexDirection::Column,
+            justify_content=JustifyContent::Center,
+            align_items=AlignItems::Center
+        >
+            <children>
+                <label text="Spawned with Commands" />
+            </children>
+        </Node>
+    );
+
+    // Markup spawned with `EntityCommands` is added to the children of the entity
+    btml!(commands.entity(hud),
+        <label text="Appended with EntityCommands" />
+    );
+
+    // `btml_children!` only accepts an entity, and gives back the ids of the new children
+    let rows: Vec<Entity> = btml_children!(commands.entity(hud),
+        for row in ["First row", "Second row"] {
+            <Text(new)>row</Text>
+        }
+    );
+    info!("Added {} rows", rows.len());
+}
@@ -0,0 +1,90 @@
+use bevy::prelude::*;
+use bevy_btml::{btml, btml_template};
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_systems(Startup, setup)
+        .run();
+}
+
+#[btml_template]
+fn menu_button(
+    parent: &mut ChildSpawnerCommands,
+    label: &str,
+    #[prop(default = Color::srgb(0.2, 0.2, 0.2))] color: Color,
+) {
+    btml!(parent,
+        <Node
+            width=Val::Px(200.0),
+            padding=UiRect::all(Val::Px(10.0)),
+            justify_content=JustifyContent::Center
+        >
+            <Button />
+            <BackgroundColor>color</BackgroundColor>
+            <children>
+                <Text(new)>label</Text>
+                <TextFont font_size=20.0 />
+                <TextColor>Color::WHITE</TextColor>
+            </children>
+        </Node>
+    );
+}
+
+#[btml_template(slots(title, default))]
+fn dialog(parent: &mut ChildSpawnerCommands, width: f32) {
+    btml!(parent,
+        <Node
+            width=Val::Px(width),
+            flex_direction=FlexDirection::Column,
+            align_items=AlignItems::Center,
+            padding=UiRect::all(Val::Px(20.0)),
+            row_gap=Val::Px(10.0)
+        >
+            <BackgroundColor>Color::srgb(0.1, 0.1, 0.1)</BackgroundColor>
+            <children>
+                // Used when the caller doesn't fill the title slot
+                <slot name="title">
+                    <Text(new)>"Menu"</Text>
+                    <TextFont font_size=30.0 />
+                </slot>
+                <slot />
+            </children>
+        </Node>
+    );
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn(Camera2d);
+
+    let extras = ["Credits", "Quit"];
+
+    btml!(commands,
+        <Node
+            width=Val::Percent(100.0),
+            height=Val::Percent(100.0),
+            flex_direction=FlexDirection::Column,
+            align_items=AlignItems::Center,
+            justify_content=JustifyContent::Center,
+            row_gap=Val::Px(10.0)
+        >
+            <children>
+                <dialog width=300.0>
+                    <slot name="title">
+                        <Text(new)>"Main Menu"</Text>
+                        <TextFont font_size=30.0 />
+                    </slot>
+                    <slot>
+                        <menu_button label="Play", color=Color::srgb(0.1, 0.5, 0.1) />
+                        <menu_button label="Options" />
+
+                        // Templates work inside loops and conditions too
+                        for extra in extras {
+                            <menu_button label=extra />
+                        }
+                    </slot>
+                </dialog>
+            </children>
+        </Node>
+    );
+}
@@ -0,0 +1,57 @@
+use bevy::prelude::*;
+use bevy_btml::btml;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_systems(Startup, setup)
+        .run();
+}
+
+/// A paginated source that hands out one page at a time and doesn't implement `IntoIterator`.
+struct Pages {
+    entries: Vec<&'static str>,
+    page_size: usize,
+    cursor: usize,
+}
+
+impl Pages {
+    fn next_page(&mut self) -> Option<&[&'static str]> {
+        if self.cursor >= self.entries.len() {
+            return None;
+        }
+        let end = (self.cursor + self.page_size).min(self.entries.len());
+        let page = &self.entries[self.cursor..end];
+        self.cursor = end;
+        Some(page)
+    }
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn(Camera2d);
+
+    let mut pages = Pages {
+        entries: vec!["Alice", "Bob", "Carol", "Dave", "Eve"],
+        page_size: 2,
+        cursor: 0,
+    };
+
+    btml!(commands,
+        <Node
+            width=Val::Percent(100.0),
+            height=Val::Percent(100.0),
+            flex_direction=FlexDirection::Column,
+            align_items=AlignItems::Center,
+            justify_content=JustifyContent::Center,
+            row_gap=Val::Px(10.0)
+        >
+            <children>
+                while let Some(page) = pages.next_page() {
+                    <Text>page.join(", ")</Text>
+                    <TextFont font_size=20.0 />
+                    <TextColor>Color::WHITE</TextColor>
+                }
+            </children>
+        </Node>
+    );
+}
@@ -0,0 +1,1536 @@
+use crate::parse::{
+    BtmlCapture, BtmlChild, BtmlElse, BtmlHandler, BtmlLet, BtmlMethod, BtmlNode, BtmlSpawnerExpr,
+    CaptureKind, Content, HandlerEvent, LoopMeta, SpawnerKind, loop_meta_ident,
+};
+use proc_macro2::TokenStream;
+use quote::{ToTokens, format_ident, quote, quote_spanned};
+use syn::{Expr, Ident, Pat};
+
+// Generated code reaches Bevy through the re-exports of the `bevy_btml` crate.
+fn ecs_path() -> TokenStream {
+    quote! { ::bevy_btml::__private::bevy_ecs }
+}
+
+/// The variable holding the current spawner, and whether it patches an existing tree.
+struct Spawner {
+    ident: Ident,
+    patch: bool,
+}
+
+impl Spawner {
+    fn root(patch: bool) -> Self {
+        Spawner {
+            ident: Ident::new("spawner", proc_macro2::Span::mixed_site()),
+            patch,
+        }
+    }
+
+    // Children are spawned in closures taking their spawner as `parent`.
+    fn parent(patch: bool) -> Self {
+        Spawner {
+            ident: Ident::new("parent", proc_macro2::Span::call_site()),
+            patch,
+        }
+    }
+
+    fn child(&self) -> Self {
+        Spawner::parent(self.patch)
+    }
+}
+
+impl ToTokens for Spawner {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.ident.to_tokens(tokens);
+    }
+}
+
+/// Generates a single bundle, where `<children>` become related entities spawned through
+/// `Children::spawn`.
+pub fn generate_bundle_code(nodes: &[BtmlChild]) -> TokenStream {
+    let mut captures = Vec::new();
+    collect_captures(nodes, &mut captures);
+    if let Some(capture) = captures.first() {
+        return quote_spanned! {capture.name.span()=>
+            compile_error!("Capturing entities is not allowed in bundle-only mode. Use a spawner.")
+        };
+    }
+
+    let mut parts = Vec::new();
+
+    for child in nodes {
+        if let Err(err) = collect_bundle_parts(child, &mut parts) {
+            return err.into_compile_error();
+        }
+    }
+
+    bundle_from_parts(&parts)
+}
+
+// Captures are looked for in control flow too, which is lowered inside `SpawnWith`.
+fn collect_captures<'a>(nodes: &'a [BtmlChild], captures: &mut Vec<&'a BtmlCapture>) {
+    for child in nodes {
+        match child {
+            BtmlChild::Node(node) => {
+                captures.extend(&node.capture);
+                collect_captures(&node.children, captures);
+            }
+            BtmlChild::For(for_loop) => {
+                collect_captures(&for_loop.body, captures);
+                if let Some(else_branch) = &for_loop.else_branch {
+                    collect_captures(else_branch, captures);
+                }
+            }
+            BtmlChild::While(while_loop) => collect_captures(&while_loop.body, captures),
+            BtmlChild::If(if_node) => {
+                let mut if_node = Some(if_node);
+                while let Some(current) = if_node.take() {
+                    collect_captures(&current.then_branch, captures);
+                    match current.else_branch.as_deref() {
+                        Some(BtmlElse::If(next)) => if_node = Some(next),
+                        Some(BtmlElse::Block(block)) => collect_captures(block, captures),
+                        None => {}
+                    }
+                }
+            }
+            BtmlChild::Match(match_node) => {
+                for arm in &match_node.arms {
+                    collect_captures(&arm.body, captures);
+                }
+            }
+            BtmlChild::Let(binding) => {
+                if let Some(else_branch) = &binding.else_branch {
+                    collect_captures(else_branch, captures);
+                }
+            }
+            BtmlChild::Rust(_) => {}
+        }
+    }
+}
+
+/// A part of a bundle, in source order.
+enum BundlePart<'a> {
+    Component(TokenStream),
+    Child(TokenStream),
+    Binding(&'a BtmlLet),
+}
+
+fn collect_bundle_parts<'a>(
+    child: &'a BtmlChild,
+    parts: &mut Vec<BundlePart<'a>>,
+) -> syn::Result<()> {
+    match child {
+        BtmlChild::Node(node) if node.tag == "children" && has_let_else(&node.children) => {
+            // The whole block depends on the pattern, so it's spawned like in spawner mode.
+            let group_code = generate_group_code(&Spawner::parent(false), &node.children, None);
+            parts.push(BundlePart::Child(spawn_with(group_code)));
+        }
+        BtmlChild::Node(node) if node.tag == "children" => {
+            let mut child_parts = Vec::new();
+
+            for inner_child in &node.children {
+                collect_bundle_parts(inner_child, &mut child_parts)?;
+            }
+
+            // Like in spawner mode, a block without components doesn't get a wrapper entity.
+            let has_components = child_parts
+                .iter()
+                .any(|part| matches!(part, BundlePart::Component(_)));
+            if !has_components && !has_entity_tag(&node.children) {
+                parts.extend(child_parts);
+            } else {
+                let ecs = ecs_path();
+                let bundle = bundle_from_parts(&child_parts);
+                parts.push(BundlePart::Child(quote! { #ecs::spawn::Spawn(#bundle) }));
+            }
+        }
+        BtmlChild::Node(node) if node.tag == "slot" || is_template(node) => {
+            return Err(syn::Error::new(
+                node.tag.span(),
+                "Templates and slots are not allowed in bundle-only mode. Use a spawner.",
+            ));
+        }
+        BtmlChild::Node(node) => {
+            if let Some(handler) = node.handlers.first() {
+                return Err(syn::Error::new(
+                    handler.on.span(),
+                    "Event handlers are not allowed in bundle-only mode. Use a spawner.",
+                ));
+            }
+            if node.tag != "entity" {
+                parts.push(BundlePart::Component(node_to_component(node)));
+            } else if let Some(key) = node.attributes.first() {
+                return Err(syn::Error::new(key.key.span(), KEY_ERROR));
+            }
+            for inner_child in &node.children {
+                collect_bundle_parts(inner_child, parts)?;
+            }
+        }
+        BtmlChild::Let(binding) if binding.else_branch.is_none() => {
+            parts.push(BundlePart::Binding(binding));
+        }
+        BtmlChild::Let(binding) => {
+            return Err(syn::Error::new_spanned(
+                &binding.pat,
+                "In bundle-only mode, `let ... else` can only be used inside a `<children>` block.",
+            ));
+        }
+        BtmlChild::For(_)
+        | BtmlChild::While(_)
+        | BtmlChild::If(_)
+        | BtmlChild::Match(_)
+        | BtmlChild::Rust(_) => {
+            let child_spawns = generate_children_code(&Spawner::parent(false), &[child], None);
+            parts.push(BundlePart::Child(spawn_with(
+                quote! { #( #child_spawns; )* },
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+// Control flow runs when the bundle is spawned, with the spawner of its children as `parent`.
+fn spawn_with(code: TokenStream) -> TokenStream {
+    let ecs = ecs_path();
+    quote! {
+        #ecs::spawn::SpawnWith(move |parent: &mut #ecs::hierarchy::ChildSpawner| {
+            #code;
+        })
+    }
+}
+
+// A `let ... else` also skips the rest of the group from inside a node merged into its entity.
+fn has_let_else(nodes: &[BtmlChild]) -> bool {
+    nodes.iter().any(|child| match child {
+        BtmlChild::Let(binding) => binding.else_branch.is_some(),
+        BtmlChild::Node(node)
+            if node.tag == "children" || node.tag == "slot" || is_template(node) =>
+        {
+            false
+        }
+        BtmlChild::Node(node) => has_let_else(&node.children),
+        _ => false,
+    })
+}
+
+// The parts before the last binding are evaluated at their position, so the bindings after them
+// can't shadow or move the values they use.
+fn bundle_from_parts(parts: &[BundlePart]) -> TokenStream {
+    let last_binding = parts
+        .iter()
+        .rposition(|part| matches!(part, BundlePart::Binding(_)));
+    let mut statements = Vec::new();
+    let mut components = Vec::new();
+    let mut children = Vec::new();
+
+    for (index, part) in parts.iter().enumerate() {
+        let hoisted = last_binding.is_some_and(|last| index < last);
+        let value = format_ident!("value_{}", index, span = proc_macro2::Span::mixed_site());
+        match part {
+            BundlePart::Component(component) => {
+                components.push(hoist(&mut statements, hoisted, value, component));
+            }
+            BundlePart::Child(child) => {
+                children.push(hoist(&mut statements, hoisted, value, child));
+            }
+            BundlePart::Binding(binding) => statements.push(let_statement(binding)),
+        }
+    }
+
+    if !children.is_empty() {
+        let ecs = ecs_path();
+        let list = spawnable_list(children);
+        components.push(quote! {
+            <#ecs::hierarchy::Children as #ecs::spawn::SpawnRelated>::spawn(#list)
+        });
+    }
+
+    let bundle = quote! {
+        (
+            #( #components ),*
+        )
+    };
+
+    if statements.is_empty() {
+        bundle
+    } else {
+        quote! {
+            {
+                #( #statements )*
+                #bundle
+            }
+        }
+    }
+}
+
+// Stores a value in a variable at its position when a binding comes after it.
+fn hoist(
+    statements: &mut Vec<TokenStream>,
+    hoisted: bool,
+    ident: Ident,
+    value: &TokenStream,
+) -> TokenStream {
+    if hoisted {
+        statements.push(quote! { let #ident = #value; });
+        ident.into_token_stream()
+    } else {
+        value.clone()
+    }
+}
+
+fn let_statement(binding: &BtmlLet) -> TokenStream {
+    let pat = &binding.pat;
+    let ty = binding.ty.as_ref().map(|ty| quote! { : #ty });
+    let expr = &binding.expr;
+    quote! { let #pat #ty = #expr; }
+}
+
+// The value matched by a `let ... else`, with its type annotation.
+fn let_else_value(binding: &BtmlLet) -> TokenStream {
+    let expr = &binding.expr;
+    match &binding.ty {
+        Some(ty) => quote! { { let value: #ty = #expr; value } },
+        None => quote! { #expr },
+    }
+}
+
+// Spawnable lists are implemented for tuples of up to 12 elements, so longer lists are nested.
+fn spawnable_list(items: Vec<TokenStream>) -> TokenStream {
+    if items.len() <= 12 {
+        quote! { ( #( #items, )* ) }
+    } else {
+        let chunks = items
+            .chunks(12)
+            .map(|chunk| spawnable_list(chunk.to_vec()))
+            .collect();
+        spawnable_list(chunks)
+    }
+}
+
+/// Spawns every top-level node tree, and every iteration of a top-level loop, as its own root.
+/// Evaluates to the `Entity` of a single root node, or to an `Option<Entity>` when a top-level
+/// `let ... else` can skip it. Anything else evaluates to a `Vec<Entity>` of all the roots,
+/// including the entities returned by top-level templates.
+pub fn generate_root_code(spawner_expr: &BtmlSpawnerExpr, nodes: &[BtmlChild]) -> TokenStream {
+    let spawner = &Spawner::root(false);
+    let mut roots = Vec::new();
+
+    for child in nodes {
+        match child {
+            BtmlChild::Let(_) => {}
+            BtmlChild::Node(node) if node.tag == "children" => {
+                return quote_spanned! {node.tag.span()=>
+                    compile_error!("`<children>` needs a parent node. Wrap it in a node or an `<entity>`.")
+                };
+            }
+            _ => roots.push(child),
+        }
+    }
+
+    let is_single = matches!(
+        roots.as_slice(),
+        [BtmlChild::Node(node)] if node.tag != "slot" && !is_template(node)
+    );
+    let kind = if !is_single {
+        CaptureKind::Many
+    } else if nodes
+        .iter()
+        .any(|child| matches!(child, BtmlChild::Let(binding) if binding.else_branch.is_some()))
+    {
+        CaptureKind::Optional
+    } else {
+        CaptureKind::Single
+    };
+    let root = BtmlCapture {
+        name: Ident::new("roots", proc_macro2::Span::mixed_site()),
+        kind,
+    };
+    let spawn_code = generate_sequence_code(spawner, nodes, Some(&root));
+
+    let name = &root.name;
+    let ecs = ecs_path();
+    // Typed, since only templates could be spawned at the top level.
+    let (declaration, value) = match kind {
+        CaptureKind::Single => (quote! { let mut #name = None; }, quote! { #name.unwrap() }),
+        CaptureKind::Optional => (quote! { let mut #name = None; }, quote! { #name }),
+        CaptureKind::Many => (
+            quote! { let mut #name: Vec<#ecs::entity::Entity> = Vec::new(); },
+            quote! { #name },
+        ),
+    };
+
+    // The children are despawned by the same spawner, right before the new ones are spawned.
+    let replace = (spawner_expr.kind == SpawnerKind::Replace).then(|| {
+        quote! { ::bevy_btml::__private::BtmlEntity::despawn_btml_children(#spawner); }
+    });
+
+    borrow_spawner(
+        spawner_expr,
+        spawner,
+        quote! {
+            #declaration
+            #replace
+            #spawn_code;
+            #value
+        },
+    )
+}
+
+/// Inserts the components on an existing entity, and spawns the `<children>` and control flow as
+/// its children.
+pub fn generate_insert_code(spawner_expr: &BtmlSpawnerExpr, nodes: &[BtmlChild]) -> TokenStream {
+    let spawner = &Spawner::root(false);
+    let mut parts = Vec::new();
+    let mut captures = Vec::new();
+
+    for child in nodes {
+        collect_entity_parts(child, &mut parts, &mut captures);
+    }
+
+    if let Some(capture) = captures.first() {
+        return quote_spanned! {capture.name.span()=>
+            compile_error!("Inserted components belong to an existing entity, only its new children can be captured.")
+        };
+    }
+    if let Some(key) = find_key(nodes) {
+        return key_error(key);
+    }
+
+    let handlers = find_handlers(nodes);
+    if let Some(handler) = handlers
+        .iter()
+        .find(|handler| matches!(handler.event, HandlerEvent::Spawn))
+    {
+        return syn::Error::new(
+            handler.on.span(),
+            "`btml_insert!` doesn't spawn the entity, only its new children can have `on_spawn`.",
+        )
+        .to_compile_error();
+    }
+
+    let finish = |values: EntityValues| {
+        let EntityValues {
+            components,
+            handlers,
+            children,
+            ..
+        } = values;
+        let entity = Ident::new("entity", proc_macro2::Span::mixed_site());
+        let observe_code = (!handlers.is_empty()).then(|| {
+            let observe = handler_calls(spawner, &entity, &handlers);
+            quote! {
+                let #entity = #spawner.id();
+                #observe
+            }
+        });
+
+        quote! {
+            ::bevy_btml::__private::BtmlEntity::insert_btml(#spawner, (
+                #( #components ),*
+            ));
+            #observe_code
+            #( #children; )*
+        }
+    };
+
+    // The new children are spawned by the entity itself.
+    let defer = |closure: TokenStream| {
+        quote! { ::bevy_btml::__private::defer_btml(&*#spawner, #closure) }
+    };
+    let insert_code =
+        generate_parts_code(spawner, &defer, &parts, EntityValues::default(), &finish);
+
+    borrow_spawner(spawner_expr, spawner, insert_code)
+}
+
+// The spawner is borrowed through a trait checking that it fits the macro.
+fn borrow_spawner(
+    spawner_expr: &BtmlSpawnerExpr,
+    spawner: &Spawner,
+    code: TokenStream,
+) -> TokenStream {
+    let expr = &spawner_expr.expr;
+    let (borrow_trait, borrow) = match spawner_expr.kind {
+        SpawnerKind::Any => (
+            quote! { ::bevy_btml::BtmlSpawner },
+            quote! { as_btml_spawner },
+        ),
+        SpawnerKind::World => (
+            quote! { ::bevy_btml::__private::AsBtmlSpawner },
+            quote! { as_btml_world },
+        ),
+        SpawnerKind::Entity | SpawnerKind::Replace => (
+            quote! { ::bevy_btml::__private::AsBtmlSpawner },
+            quote! { as_btml_entity },
+        ),
+        SpawnerKind::Patch => (
+            quote! { ::bevy_btml::__private::AsBtmlSpawner },
+            quote! { as_btml_patch_target },
+        ),
+    };
+
+    // Matching keeps the temporaries of the spawner expression, like `commands.entity(e)`, alive.
+    quote! {
+        {
+            use #borrow_trait as _;
+            match (#expr).#borrow() {
+                #spawner => {
+                    #code
+                }
+            }
+        }
+    }
+}
+
+/// Patches the existing entity `root` and its descendants to match the markup: components are
+/// only inserted again when their value changed, missing children are spawned and the children
+/// that aren't in the markup anymore are despawned.
+pub fn generate_patch_code(
+    target_expr: &BtmlSpawnerExpr,
+    root_expr: &Expr,
+    nodes: &[BtmlChild],
+) -> TokenStream {
+    let spawner = &Spawner::root(true);
+    let mut parts = Vec::new();
+    let mut captures = Vec::new();
+
+    for child in nodes {
+        collect_entity_parts(child, &mut parts, &mut captures);
+    }
+
+    if let Some(key) = find_key(nodes) {
+        return key_error(key);
+    }
+    if let Some(handler) = find_handlers(nodes).first() {
+        return syn::Error::new(
+            handler.on.span(),
+            "The patched entity already exists, only its new children can get event handlers.",
+        )
+        .to_compile_error();
+    }
+
+    let finish = |values: EntityValues| {
+        let (bundle, patch) = patch_components(&values.components);
+        let children = &values.children;
+
+        quote! {
+            ::bevy_btml::__private::BtmlPatcher::patch_parent(#spawner, #bundle, #patch);
+            #( #children; )*
+        }
+    };
+    // The children of the root are patched by the patcher of the root itself.
+    let defer = |closure: TokenStream| {
+        quote! { ::bevy_btml::__private::defer_btml(&*#spawner, #closure) }
+    };
+    let patch_code = generate_parts_code(spawner, &defer, &parts, EntityValues::default(), &finish);
+
+    let ecs = ecs_path();
+    let target = &Spawner {
+        ident: Ident::new("target", proc_macro2::Span::mixed_site()),
+        patch: true,
+    };
+    let root = Ident::new("root", proc_macro2::Span::mixed_site());
+    let patcher = Ident::new("patcher", proc_macro2::Span::mixed_site());
+    let world = Ident::new("world", proc_macro2::Span::mixed_site());
+
+    // With `Commands`, the values used by the markup are moved into a command.
+    let code = borrow_spawner(
+        target_expr,
+        target,
+        quote! {
+            ::bevy_btml::__private::BtmlPatchTarget::patch_btml(#target, move |#world: &mut #ecs::world::World| {
+                let Some(mut #patcher) = ::bevy_btml::__private::BtmlPatcher::new(#world, #root) else {
+                    return;
+                };
+                let #spawner = &mut #patcher;
+                #patch_code;
+            })
+        },
+    );
+
+    quote! {
+        {
+            let #root: #ecs::entity::Entity = #root_expr;
+            #code
+        }
+    }
+}
+
+// The components are bundled for new entities, and compared one by one with existing ones.
+fn patch_components(components: &[TokenStream]) -> (TokenStream, TokenStream) {
+    let values: Vec<Ident> = (0..components.len())
+        .map(|i| format_ident!("component_{}", i, span = proc_macro2::Span::mixed_site()))
+        .collect();
+    let entity = if values.is_empty() {
+        quote! { _ }
+    } else {
+        Ident::new("entity", proc_macro2::Span::mixed_site()).into_token_stream()
+    };
+
+    (
+        quote! { ( #( #components, )* ) },
+        quote! {
+            |#entity, ( #( #values, )* )| {
+                #( ::bevy_btml::__private::patch_component(#entity, #values); )*
+            }
+        },
+    )
+}
+
+const KEY_ERROR: &str =
+    "Keys identify the children of a patched tree, they are only used by `btml_patch!`";
+
+fn key_error(key: &Expr) -> TokenStream {
+    syn::Error::new_spanned(key, KEY_ERROR).to_compile_error()
+}
+
+// The key of an entity is given to the `<entity>` grouping its components.
+fn find_key(nodes: &[BtmlChild]) -> Option<&Expr> {
+    nodes.iter().find_map(|child| match child {
+        BtmlChild::Node(node) if node.tag == "entity" => node
+            .attributes
+            .iter()
+            .find(|a| a.key == "key")
+            .map(|a| &a.value)
+            .or_else(|| find_key(&node.children)),
+        BtmlChild::Node(node)
+            if node.tag != "children" && node.tag != "slot" && !is_template(node) =>
+        {
+            find_key(&node.children)
+        }
+        _ => None,
+    })
+}
+
+// Like its components, the event handlers of an entity can be spread over the nodes of its group.
+fn find_handlers(nodes: &[BtmlChild]) -> Vec<&BtmlHandler> {
+    let mut handlers = Vec::new();
+    for child in nodes {
+        if let BtmlChild::Node(node) = child
+            && node.tag != "children"
+            && node.tag != "slot"
+            && !is_template(node)
+        {
+            handlers.extend(&node.handlers);
+            handlers.extend(find_handlers(&node.children));
+        }
+    }
+    handlers
+}
+
+fn handler_calls(
+    spawner: &Spawner,
+    entity: &Ident,
+    handlers: &[(&HandlerEvent, TokenStream)],
+) -> TokenStream {
+    let ecs = ecs_path();
+    let calls = handlers.iter().map(|(event, handler)| {
+        let (event, component) = match event {
+            HandlerEvent::Spawn => {
+                return quote! {
+                    ::bevy_btml::BtmlSpawner::on_spawn_btml(#spawner, #entity, #handler);
+                };
+            }
+            // Lifecycle events only reach the observers of one of the components involved.
+            HandlerEvent::Lifecycle { event, component } => (
+                quote_spanned! {event.span()=> #ecs::lifecycle::#event },
+                component.to_token_stream(),
+            ),
+            HandlerEvent::Pointer(name) => (
+                quote_spanned! {name.span()=>
+                    ::bevy_btml::__private::bevy_picking::events::Pointer<
+                        ::bevy_btml::__private::bevy_picking::events::#name
+                    >
+                },
+                quote! { _ },
+            ),
+            HandlerEvent::Type(ty) => (ty.to_token_stream(), quote! { _ }),
+        };
+        quote! {
+            ::bevy_btml::BtmlSpawner::observe_btml::<#event, #component, _>(#spawner, #entity, #handler);
+        }
+    });
+    quote! { #( #calls )* }
+}
+
+fn generate_entity_code(
+    spawner: &Spawner,
+    nodes: &[BtmlChild],
+    root: Option<&BtmlCapture>,
+) -> TokenStream {
+    let mut parts = Vec::new();
+    let mut values = EntityValues::default();
+
+    for child in nodes {
+        collect_entity_parts(child, &mut parts, &mut values.captures);
+    }
+    values.captures.extend(root);
+
+    let finish = |values: EntityValues| {
+        let EntityValues {
+            components,
+            key,
+            handlers,
+            captures,
+            children,
+        } = values;
+
+        let spawn_code = if spawner.patch {
+            let key = match key {
+                Some((_, key)) => quote! { Some(::bevy_btml::__private::patch_key(&#key)) },
+                None => quote! { None },
+            };
+            let (bundle, patch) = patch_components(&components);
+
+            if children.is_empty() {
+                quote! {
+                    ::bevy_btml::__private::BtmlPatcher::patch(#spawner, #key, #bundle, #patch)
+                }
+            } else {
+                quote! {
+                    ::bevy_btml::__private::BtmlPatcher::patch_with_children(#spawner, #key, #bundle, #patch, |parent| {
+                        #( #children; )*
+                    })
+                }
+            }
+        } else if let Some((key, _)) = key {
+            return key_error(key);
+        } else if children.is_empty() {
+            quote! {
+                ::bevy_btml::BtmlSpawner::spawn_btml(#spawner, (
+                    #( #components ),*
+                ))
+            }
+        } else {
+            quote! {
+                ::bevy_btml::BtmlSpawner::spawn_btml_with_children(#spawner, (
+                    #( #components ),*
+                ), |parent| {
+                    #( #children; )*
+                })
+            }
+        };
+
+        if captures.is_empty() && handlers.is_empty() {
+            spawn_code
+        } else {
+            let entity = Ident::new("entity", proc_macro2::Span::mixed_site());
+            let handle = handler_calls(spawner, &entity, &handlers);
+            let assignments = captures.iter().map(|capture| {
+                let name = &capture.name;
+                match capture.kind {
+                    CaptureKind::Single | CaptureKind::Optional => {
+                        quote! { #name = Some(#entity); }
+                    }
+                    CaptureKind::Many => quote! { #name.push(#entity); },
+                }
+            });
+
+            quote! {
+                {
+                    let #entity = #spawn_code;
+                    #handle
+                    #( #assignments )*
+                    #entity
+                }
+            }
+        }
+    };
+
+    let defer = |closure: TokenStream| {
+        quote! { ::bevy_btml::BtmlSpawner::defer_btml_children(&*#spawner, #closure) }
+    };
+    generate_parts_code(&spawner.child(), &defer, &parts, values, &finish)
+}
+
+// The closure spawning the children before a binding, and its call once their parent exists. The
+// captures set by the children are moved in and out of the closure, so it doesn't borrow them
+// while the children after the binding set them too.
+fn defer_children(
+    children_spawner: &Spawner,
+    children: &[&BtmlChild],
+    closure: &Ident,
+) -> (TokenStream, TokenStream) {
+    let mut captures: Vec<&BtmlCapture> = Vec::new();
+    for child in children {
+        collect_captures(std::slice::from_ref(*child), &mut captures);
+    }
+    let mut names = Vec::new();
+    let mut types = Vec::new();
+    let ecs = ecs_path();
+    for capture in captures {
+        if !names.contains(&&capture.name) {
+            names.push(&capture.name);
+            types.push(match capture.kind {
+                CaptureKind::Single | CaptureKind::Optional => {
+                    quote! { Option<#ecs::entity::Entity> }
+                }
+                CaptureKind::Many => quote! { Vec<#ecs::entity::Entity> },
+            });
+        }
+    }
+
+    let child_spawns = generate_children_code(children_spawner, children, None);
+    let returned = (!names.is_empty()).then(|| quote! { ( #( #names, )* ) });
+    let closure_code = quote! {
+        |#children_spawner, ( #( mut #names, )* ): ( #( #types, )* )| {
+            #( #child_spawns; )*
+            #returned
+        }
+    };
+    let call = if names.is_empty() {
+        quote! { #closure(#children_spawner, ()) }
+    } else {
+        quote! {
+            ( #( #names, )* ) = #closure(#children_spawner, ( #( ::core::mem::take(&mut #names), )* ))
+        }
+    };
+
+    (closure_code, call)
+}
+
+/// A part of the entity of a group, in source order.
+enum EntityPart<'a> {
+    Component(TokenStream),
+    Key(&'a Expr),
+    Handler(&'a BtmlHandler),
+    Children(&'a BtmlChild),
+    Binding(&'a BtmlLet),
+}
+
+/// The values of the parts of an entity, along one path through its `let ... else` bindings.
+#[derive(Clone, Default)]
+struct EntityValues<'a> {
+    components: Vec<TokenStream>,
+    key: Option<(&'a Expr, TokenStream)>,
+    handlers: Vec<(&'a HandlerEvent, TokenStream)>,
+    // Captures name the entity itself, so they're set whichever parts are spawned.
+    captures: Vec<&'a BtmlCapture>,
+    // Statements spawning the children with `children_spawner`.
+    children: Vec<TokenStream>,
+}
+
+// The parts before the last binding are evaluated at their position, so the bindings after them
+// can't shadow or move the values they use. The children spawned before a binding are deferred to
+// closures called once the entity exists, and a failing `let ... else` puts the parts of its
+// `else` markup in place of the parts after it.
+fn generate_parts_code<'a>(
+    children_spawner: &Spawner,
+    defer: &dyn Fn(TokenStream) -> TokenStream,
+    parts: &[EntityPart<'a>],
+    mut values: EntityValues<'a>,
+    finish: &dyn Fn(EntityValues<'a>) -> TokenStream,
+) -> TokenStream {
+    let last_binding = parts
+        .iter()
+        .rposition(|part| matches!(part, EntityPart::Binding(_)));
+    let mut statements = Vec::new();
+    let mut children = Vec::new();
+
+    for (index, part) in parts.iter().enumerate() {
+        let hoisted = last_binding.is_some_and(|last| index < last);
+        let mixed_site = proc_macro2::Span::mixed_site();
+
+        match part {
+            EntityPart::Component(component) => {
+                let value = format_ident!("value_{}", values.components.len(), span = mixed_site);
+                let component = hoist(&mut statements, hoisted, value, component);
+                values.components.push(component);
+            }
+            EntityPart::Key(key) => {
+                let value = Ident::new("key", mixed_site);
+                let value = hoist(&mut statements, hoisted, value, &key.to_token_stream());
+                values.key = Some((key, value));
+            }
+            EntityPart::Handler(BtmlHandler { event, handler, .. }) => {
+                let value = format_ident!("handler_{}", values.handlers.len(), span = mixed_site);
+                let handler = hoist(&mut statements, hoisted, value, &handler.to_token_stream());
+                values.handlers.push((event, handler));
+            }
+            EntityPart::Children(child) => children.push(*child),
+            EntityPart::Binding(binding) => {
+                if !children.is_empty() {
+                    let value =
+                        format_ident!("children_{}", values.children.len(), span = mixed_site);
+                    let (closure, call) = defer_children(children_spawner, &children, &value);
+                    let deferred = defer(closure);
+                    statements.push(quote! { let #value = #deferred; });
+                    values.children.push(call);
+                    children.clear();
+                }
+
+                let Some(else_branch) = &binding.else_branch else {
+                    statements.push(let_statement(binding));
+                    continue;
+                };
+
+                let rest = &parts[index + 1..];
+                let rest_code =
+                    generate_parts_code(children_spawner, defer, rest, values.clone(), finish);
+
+                let mut else_parts = Vec::new();
+                for child in else_branch {
+                    collect_entity_parts(child, &mut else_parts, &mut values.captures);
+                }
+                let else_code =
+                    generate_parts_code(children_spawner, defer, &else_parts, values, finish);
+
+                let pat = &binding.pat;
+                let value = let_else_value(binding);
+                return quote! {
+                    {
+                        #( #statements )*
+                        if let #pat = #value {
+                            #rest_code
+                        } else {
+                            #else_code
+                        }
+                    }
+                };
+            }
+        }
+    }
+
+    let child_spawns = generate_children_code(children_spawner, &children, None);
+    values.children.extend(child_spawns);
+    let finish_code = finish(values);
+
+    quote! {
+        {
+            #( #statements )*
+            #finish_code
+        }
+    }
+}
+
+/// Gives every `#name` capture its kind and returns the variable declarations to put before the
+/// spawn code, along with the statements that unwrap the captures that are always spawned.
+pub fn generate_captures(nodes: &mut [BtmlChild]) -> syn::Result<(TokenStream, TokenStream)> {
+    let mut captures = Vec::new();
+    resolve_captures(nodes, CaptureKind::Single, &mut captures)?;
+
+    let declarations = captures.iter().map(|(name, kind)| match kind {
+        CaptureKind::Single | CaptureKind::Optional => quote! { let mut #name = None; },
+        CaptureKind::Many => quote! { let mut #name = Vec::new(); },
+    });
+    let unwraps = captures
+        .iter()
+        .filter(|(_, kind)| *kind == CaptureKind::Single)
+        .map(|(name, _)| quote! { let #name = #name.unwrap(); });
+
+    Ok((quote! { #( #declarations )* }, quote! { #( #unwraps )* }))
+}
+
+fn resolve_captures(
+    children: &mut [BtmlChild],
+    kind: CaptureKind,
+    captures: &mut Vec<(Ident, CaptureKind)>,
+) -> syn::Result<()> {
+    let mut kind = kind;
+
+    for child in children {
+        let branch_kind = kind.max(CaptureKind::Optional);
+        match child {
+            BtmlChild::Node(node) => {
+                let spawns_entity =
+                    node.tag != "children" && node.tag != "slot" && !is_template(node);
+                if let Some(capture) = &mut node.capture {
+                    if !spawns_entity {
+                        return Err(syn::Error::new(
+                            capture.name.span(),
+                            "Only nodes that spawn an entity can be captured, not templates, slots or `<children>`",
+                        ));
+                    }
+                    capture.kind = kind;
+
+                    match captures.iter().find(|(name, _)| *name == capture.name) {
+                        Some((_, existing)) if *existing != kind => {
+                            return Err(syn::Error::new(
+                                capture.name.span(),
+                                format!(
+                                    "`{}` is already captured by a node that is spawned a different number of times",
+                                    capture.name
+                                ),
+                            ));
+                        }
+                        Some(_) => {}
+                        None => captures.push((capture.name.clone(), kind)),
+                    }
+                }
+                // A template can skip the markup given to a slot, and the fallback of a slot is
+                // only spawned when the caller leaves it empty.
+                let children_kind = if node.tag == "slot" || is_template(node) {
+                    branch_kind
+                } else {
+                    kind
+                };
+                resolve_captures(&mut node.children, children_kind, captures)?;
+            }
+            BtmlChild::For(for_loop) => {
+                resolve_captures(&mut for_loop.body, CaptureKind::Many, captures)?;
+                if let Some(else_branch) = &mut for_loop.else_branch {
+                    resolve_captures(else_branch, branch_kind, captures)?;
+                }
+            }
+            BtmlChild::While(while_loop) => {
+                resolve_captures(&mut while_loop.body, CaptureKind::Many, captures)?;
+            }
+            BtmlChild::If(if_node) => {
+                let mut if_node = Some(if_node);
+                while let Some(current) = if_node.take() {
+                    resolve_captures(&mut current.then_branch, branch_kind, captures)?;
+                    match current.else_branch.as_deref_mut() {
+                        Some(BtmlElse::If(next)) => if_node = Some(next),
+                        Some(BtmlElse::Block(block)) => {
+                            resolve_captures(block, branch_kind, captures)?;
+                        }
+                        None => {}
+                    }
+                }
+            }
+            BtmlChild::Match(match_node) => {
+                for arm in &mut match_node.arms {
+                    resolve_captures(&mut arm.body, branch_kind, captures)?;
+                }
+            }
+            BtmlChild::Let(binding) => {
+                if let Some(else_branch) = &mut binding.else_branch {
+                    resolve_captures(else_branch, branch_kind, captures)?;
+                }
+            }
+            BtmlChild::Rust(_) => {}
+        }
+
+        // A failing `let ... else` skips the siblings after it, so they might not be spawned.
+        if has_let_else(std::slice::from_ref(child)) {
+            kind = branch_kind;
+        }
+    }
+
+    Ok(())
+}
+
+// A nested group (`<children>` block, loop body, branch or arm) without components of its own
+// spawns its templates and control flow directly instead of under an empty wrapper entity. An
+// explicit `<entity>` is always spawned, so its capture and handlers have an entity to target.
+fn generate_group_code(
+    spawner: &Spawner,
+    nodes: &[BtmlChild],
+    root: Option<&BtmlCapture>,
+) -> TokenStream {
+    let mut parts = Vec::new();
+    let mut captures = Vec::new();
+
+    for child in nodes {
+        collect_entity_parts(child, &mut parts, &mut captures);
+    }
+
+    let has_components = parts
+        .iter()
+        .any(|part| matches!(part, EntityPart::Component(_)));
+    if has_components || has_entity_tag(nodes) {
+        generate_entity_code(spawner, nodes, root)
+    } else {
+        generate_sequence_code(spawner, nodes, root)
+    }
+}
+
+// Spawns each node tree, template and control flow on its own, in source order. A binding is
+// visible to the siblings after it, and a failing `let ... else` spawns its `else` markup
+// instead of them.
+fn generate_sequence_code(
+    spawner: &Spawner,
+    nodes: &[BtmlChild],
+    root: Option<&BtmlCapture>,
+) -> TokenStream {
+    let mut statements = Vec::new();
+
+    for (index, child) in nodes.iter().enumerate() {
+        match child {
+            BtmlChild::Let(binding) => match &binding.else_branch {
+                None => statements.push(let_statement(binding)),
+                Some(else_branch) => {
+                    let pat = &binding.pat;
+                    let value = let_else_value(binding);
+                    let rest = generate_sequence_code(spawner, &nodes[index + 1..], root);
+                    let else_code = generate_group_code(spawner, else_branch, root);
+                    statements.push(quote! {
+                        if let #pat = #value {
+                            #rest
+                        } else {
+                            #else_code;
+                        }
+                    });
+                    break;
+                }
+            },
+            BtmlChild::Node(node)
+                if node.tag != "children" && node.tag != "slot" && !is_template(node) =>
+            {
+                let spawn_code = generate_entity_code(spawner, std::slice::from_ref(child), root);
+                statements.push(quote! { #spawn_code; });
+            }
+            _ => {
+                let child_spawns = generate_children_code(spawner, &[child], root);
+                statements.push(quote! { #( #child_spawns; )* });
+            }
+        }
+    }
+
+    quote! {
+        {
+            #( #statements )*
+        }
+    }
+}
+
+fn generate_children_code(
+    spawner: &Spawner,
+    children_generators: &[&BtmlChild],
+    root: Option<&BtmlCapture>,
+) -> Vec<TokenStream> {
+    let mut child_spawns = Vec::new();
+
+    for generator in children_generators {
+        match generator {
+            BtmlChild::Node(node) if is_template(node) => {
+                let template_code = generate_template_code(spawner, node);
+                child_spawns.push(match root {
+                    Some(root) => record_template_roots(root, template_code),
+                    None => template_code,
+                });
+            }
+            BtmlChild::Node(node) if node.tag == "slot" => {
+                child_spawns.push(generate_slot_code(spawner, node, root));
+            }
+            BtmlChild::Node(child_block) => {
+                let child_code = generate_group_code(spawner, &child_block.children, root);
+                child_spawns.push(child_code);
+            }
+            BtmlChild::For(for_loop) => {
+                let pat = &for_loop.pat;
+                let expr = &for_loop.expr;
+                let body = &for_loop.body;
+
+                let body_code = generate_group_code(spawner, body, root);
+                let (loop_head, loop_meta) = generate_for_head(pat, expr, &for_loop.meta);
+
+                let loop_code = match &for_loop.else_branch {
+                    Some(else_branch) => {
+                        // The else markup is only spawned when the loop didn't run a single time.
+                        let is_empty = Ident::new("is_empty", proc_macro2::Span::mixed_site());
+                        let else_code = generate_group_code(spawner, else_branch, root);
+                        quote! {
+                            {
+                                let mut #is_empty = true;
+                                #loop_head {
+                                    #loop_meta
+                                    #is_empty = false;
+                                    #body_code;
+                                }
+                                if #is_empty {
+                                    #else_code;
+                                }
+                            }
+                        }
+                    }
+                    None => quote! {
+                        {
+                            #loop_head {
+                                #loop_meta
+                                #body_code;
+                            }
+                        }
+                    },
+                };
+                child_spawns.push(loop_code);
+            }
+            BtmlChild::While(while_loop) => {
+                let condition = &while_loop.condition;
+                let body_code = generate_group_code(spawner, &while_loop.body, root);
+
+                let loop_code = quote! {
+                    while #condition {
+                        #body_code;
+                    }
+                };
+                child_spawns.push(loop_code);
+            }
+            BtmlChild::If(if_node) => {
+                let if_code = generate_if_code(spawner, if_node, root);
+                child_spawns.push(if_code);
+            }
+            BtmlChild::Match(match_node) => {
+                let match_code = generate_match_code(spawner, match_node, root);
+                child_spawns.push(match_code);
+            }
+            BtmlChild::Rust(rust) => {
+                let binding = rust
+                    .binding
+                    .clone()
+                    .unwrap_or_else(|| Ident::new("parent", proc_macro2::Span::call_site()));
+                let stmts = &rust.stmts;
+
+                child_spawns.push(quote! {
+                    {
+                        #[allow(unused_variables)]
+                        let #binding: &mut _ = #spawner;
+                        #( #stmts )*
+                    }
+                });
+            }
+            BtmlChild::Let(_) => unreachable!("bindings are spawned in sequence"),
+        }
+    }
+
+    child_spawns
+}
+
+// At the top level, the entities returned by a template are roots too.
+fn record_template_roots(root: &BtmlCapture, template_code: TokenStream) -> TokenStream {
+    let name = &root.name;
+    let roots = quote! { ::bevy_btml::__private::BtmlRoots::into_btml_roots(#template_code) };
+
+    match root.kind {
+        CaptureKind::Many => quote! { #name.extend(#roots) },
+        CaptureKind::Single | CaptureKind::Optional => {
+            quote! { #name = #name.or(#roots.into_iter().next()) }
+        }
+    }
+}
+
+fn generate_if_code(
+    spawner: &Spawner,
+    if_node: &crate::parse::BtmlIf,
+    root: Option<&BtmlCapture>,
+) -> TokenStream {
+    let condition = &if_node.condition;
+    let then_code = generate_group_code(spawner, &if_node.then_branch, root);
+
+    match &if_node.else_branch {
+        None => {
+            quote! {
+                if #condition {
+                    #then_code;
+                }
+            }
+        }
+        Some(else_branch) => {
+            let else_code = match &**else_branch {
+                BtmlElse::Block(block) => {
+                    let block_code = generate_group_code(spawner, block, root);
+                    quote! { #block_code; }
+                }
+                BtmlElse::If(else_if) => generate_if_code(spawner, else_if, root),
+            };
+
+            quote! {
+                if #condition {
+                    #then_code;
+                } else {
+                    #else_code
+                }
+            }
+        }
+    }
+}
+
+fn generate_match_code(
+    spawner: &Spawner,
+    match_node: &crate::parse::BtmlMatch,
+    root: Option<&BtmlCapture>,
+) -> TokenStream {
+    let expr = &match_node.expr;
+
+    let arms = match_node.arms.iter().map(|arm| {
+        let pat = &arm.pat;
+        let guard = arm.guard.as_ref().map(|guard| quote! { if #guard });
+        let body_code = generate_group_code(spawner, &arm.body, root);
+
+        quote! {
+            #pat #guard => {
+                #body_code;
+            }
+        }
+    });
+
+    quote! {
+        match #expr {
+            #( #arms )*
+        }
+    }
+}
+
+// With loop variables the iterator is enumerated, and peeked for `$last`, before binding the pattern.
+fn generate_for_head(pat: &Pat, expr: &Expr, meta: &LoopMeta) -> (TokenStream, TokenStream) {
+    if !meta.is_used() {
+        return (quote! { for #pat in #expr }, quote! {});
+    }
+
+    let iter = Ident::new("iter", proc_macro2::Span::mixed_site());
+    let index = loop_meta_ident("index", proc_macro2::Span::call_site());
+    let first = loop_meta_ident("first", proc_macro2::Span::call_site());
+    let last = loop_meta_ident("last", proc_macro2::Span::call_site());
+
+    let peekable = meta.last.then(|| quote! { .peekable() });
+    let first_code = meta.first.then(|| quote! { let #first = #index == 0; });
+    let last_code = meta
+        .last
+        .then(|| quote! { let #last = #iter.peek().is_none(); });
+
+    let loop_head = quote! {
+        let mut #iter = ::core::iter::IntoIterator::into_iter(#expr).enumerate() #peekable;
+        while let Some((#index, #pat)) = #iter.next()
+    };
+    let loop_meta = quote! {
+        #first_code
+        #last_code
+    };
+
+    (loop_head, loop_meta)
+}
+
+fn collect_entity_parts<'a>(
+    child: &'a BtmlChild,
+    parts: &mut Vec<EntityPart<'a>>,
+    captures: &mut Vec<&'a BtmlCapture>,
+) {
+    match child {
+        BtmlChild::Node(node) => {
+            if node.tag == "children" || node.tag == "slot" || is_template(node) {
+                parts.push(EntityPart::Children(child));
+            } else {
+                if node.tag != "entity" {
+                    parts.push(EntityPart::Component(node_to_component(node)));
+                } else if let Some(key) = node.attributes.iter().find(|a| a.key == "key") {
+                    parts.push(EntityPart::Key(&key.value));
+                }
+                captures.extend(&node.capture);
+                parts.extend(node.handlers.iter().map(EntityPart::Handler));
+                for inner_child in &node.children {
+                    collect_entity_parts(inner_child, parts, captures);
+                }
+            }
+        }
+        BtmlChild::For(_)
+        | BtmlChild::While(_)
+        | BtmlChild::If(_)
+        | BtmlChild::Match(_)
+        | BtmlChild::Rust(_) => {
+            parts.push(EntityPart::Children(child));
+        }
+        BtmlChild::Let(binding) => {
+            parts.push(EntityPart::Binding(binding));
+        }
+    }
+}
+
+// Looks through the nodes merged into the same entity, like `collect_entity_parts`.
+fn has_entity_tag(nodes: &[BtmlChild]) -> bool {
+    nodes.iter().any(|child| match child {
+        BtmlChild::Node(node)
+            if node.tag == "children" || node.tag == "slot" || is_template(node) =>
+        {
+            false
+        }
+        BtmlChild::Node(node) => node.tag == "entity" || has_entity_tag(&node.children),
+        _ => false,
+    })
+}
+
+// Templates use the lowercase name of their function, components are always types.
+fn is_template(node: &BtmlNode) -> bool {
+    node.tag != "children"
+        && node.tag != "slot"
+        && node.tag != "entity"
+        && node
+            .tag
+            .to_string()
+            .starts_with(|c: char| c.is_ascii_lowercase())
+}
+
+fn generate_template_code(spawner: &Spawner, node: &BtmlNode) -> TokenStream {
+    let name = &node.tag;
+
+    if node.constructor.is_some() || node.content.is_some() || !node.flags.is_empty() {
+        return syn::Error::new(
+            name.span(),
+            format!("Template <{}> only accepts `prop=value` attributes", name),
+        )
+        .to_compile_error();
+    }
+
+    let props = node.attributes.iter().map(|a| {
+        let key = &a.key;
+        let val = &a.value;
+        quote! { .#key(#val) }
+    });
+    // Building from a local keeps the "missing required prop" error on the tag.
+    let props = quote_spanned! {name.span()=>
+        {
+            let props = #name::builder() #( #props )*;
+            props.build()
+        }
+    };
+
+    let is_slot = |child: &BtmlChild| matches!(child, BtmlChild::Node(n) if n.tag == "slot");
+    let mut slots = Vec::new();
+
+    if node.children.iter().all(is_slot) {
+        for child in &node.children {
+            if let BtmlChild::Node(slot) = child {
+                match slot_name(slot) {
+                    Ok(slot_name) => slots.push((slot_name, &slot.children[..])),
+                    Err(e) => return e.to_compile_error(),
+                }
+            }
+        }
+    } else if node.children.iter().any(is_slot) {
+        return syn::Error::new(
+            name.span(),
+            format!(
+                "Template <{}> mixes <slot> tags with other markup, wrap the default content in <slot>",
+                name
+            ),
+        )
+        .to_compile_error();
+    } else {
+        let default = Ident::new("default", name.span());
+        slots.push((default, &node.children[..]));
+    }
+
+    // The spawner is passed to the slot setters so generic templates know the type of `parent`.
+    let slots = slots.into_iter().map(|(slot_name, content)| {
+        let content_code = generate_group_code(&spawner.child(), content, None);
+        quote! {
+            .#slot_name(&*#spawner, |parent| {
+                #content_code;
+            })
+        }
+    });
+    let props_ident = Ident::new("props", proc_macro2::Span::mixed_site());
+
+    quote! {
+        {
+            let #props_ident = #props #( #slots )*;
+            #name(#spawner, #props_ident)
+        }
+    }
+}
+
+fn generate_slot_code(
+    spawner: &Spawner,
+    node: &BtmlNode,
+    root: Option<&BtmlCapture>,
+) -> TokenStream {
+    let slot_name = match slot_name(node) {
+        Ok(slot_name) => slot_name,
+        Err(e) => return e.to_compile_error(),
+    };
+    let slots = Ident::new("__slots", proc_macro2::Span::call_site());
+
+    if node.children.is_empty() {
+        quote! {
+            if let Some(slot) = #slots.#slot_name {
+                slot(#spawner);
+            }
+        }
+    } else {
+        let fallback = generate_group_code(spawner, &node.children, root);
+        quote! {
+            if let Some(slot) = #slots.#slot_name {
+                slot(#spawner);
+            } else {
+                #fallback;
+            }
+        }
+    }
+}
+
+fn slot_name(node: &BtmlNode) -> syn::Result<Ident> {
+    match node.attributes.iter().find(|a| a.key == "name") {
+        Some(attr) => match &attr.value {
+            syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Str(name),
+                ..
+            }) => name.parse(),
+            value => Err(syn::Error::new_spanned(
+                value,
+                "Slot names must be string literals",
+            )),
+        },
+        None => Ok(Ident::new("default", node.tag.span())),
+    }
+}
+
+fn node_to_component(node: &BtmlNode) -> TokenStream {
+    let component = construct_component(node);
+    let methods = node.methods.iter().map(|BtmlMethod { method, value }| {
+        quote! { .#method(#value) }
+    });
+
+    quote! { #component #( #methods )* }
+}
+
+fn construct_component(node: &BtmlNode) -> TokenStream {
+    let name = &node.tag;
+
+    let has_default = node.flags.iter().any(|f| *f == "default");
+    let has_no_default = node.flags.iter().any(|f| *f == "no_default");
+
+    if has_no_default && has_default {
+        panic!("Cannot use both 'default' and 'no_default' attributes on the same component.");
+    }
+
+    if let Some(constructor) = &node.constructor {
+        match &node.content {
+            Some(Content::Arguments(args)) => {
+                quote! { #name::#constructor(#args) }
+            }
+            None => {
+                quote! { #name::#constructor() }
+            }
+        }
+    } else if let Some(content) = &node.content {
+        match content {
+            Content::Arguments(args) => {
+                quote! { #name(#args) }
+            }
+        }
+    } else if !node.attributes.is_empty() || node.base.is_some() {
+        let attrs = node.attributes.iter().map(|a| {
+            let key = &a.key;
+            let val = &a.value;
+            quote! { #key: #val }
+        });
+
+        // The base is cloned, so the same style can be spread in loops and over many nodes.
+        if let Some(base) = &node.base {
+            quote! {
+                #name {
+                    #( #attrs, )*
+                    ..(#base).clone()
+                }
+            }
+        } else if has_no_default {
+            quote! {
+                #name {
+                    #( #attrs ),*
+                }
+            }
+        } else {
+            quote! {
+                #name {
+                    #( #attrs ),*,
+                    ..Default::default()
+                }
+            }
+        }
+    } else if has_default {
+        quote! { #name::default() }
+    } else {
+        quote! {
+            #name
+        }
+    }
+}
@@ -0,0 +1,171 @@
+//! Procedural macros for [`bevy_btml`](https://docs.rs/bevy_btml).
+//!
+//! Use them through the `bevy_btml` crate, the generated code relies on its re-exports.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{DeriveInput, ItemFn, parse_macro_input};
+
+mod codegen;
+mod parse;
+mod props;
+mod template;
+
+use parse::{BtmlChild, BtmlInput, BtmlPatchInput, BtmlSpawnerExpr, SpawnerKind};
+use template::TemplateArgs;
+
+#[proc_macro]
+pub fn btml(input: TokenStream) -> TokenStream {
+    let input = match parse::replace_loop_meta(input.into()) {
+        Ok(input) => TokenStream::from(input),
+        Err(err) => return err.into_compile_error().into(),
+    };
+    let mut input_ast = parse_macro_input!(input as BtmlInput);
+
+    if let Some(spawner) = input_ast.spawner {
+        spawner_code(&mut input_ast.nodes, |nodes| {
+            codegen::generate_root_code(&spawner, nodes)
+        })
+        .into()
+    } else {
+        codegen::generate_bundle_code(&input_ast.nodes).into()
+    }
+}
+
+#[proc_macro]
+pub fn btml_children(input: TokenStream) -> TokenStream {
+    let input = match parse::replace_loop_meta(input.into()) {
+        Ok(input) => TokenStream::from(input),
+        Err(err) => return err.into_compile_error().into(),
+    };
+    let mut input_ast = parse_macro_input!(input as BtmlInput);
+
+    match entity_spawner(input_ast.spawner, SpawnerKind::Entity, "btml_children") {
+        Ok(spawner) => spawner_code(&mut input_ast.nodes, |nodes| {
+            codegen::generate_root_code(&spawner, nodes)
+        })
+        .into(),
+        Err(err) => err.into_compile_error().into(),
+    }
+}
+
+#[proc_macro]
+pub fn btml_replace(input: TokenStream) -> TokenStream {
+    let input = match parse::replace_loop_meta(input.into()) {
+        Ok(input) => TokenStream::from(input),
+        Err(err) => return err.into_compile_error().into(),
+    };
+    let mut input_ast = parse_macro_input!(input as BtmlInput);
+
+    match entity_spawner(input_ast.spawner, SpawnerKind::Replace, "btml_replace") {
+        Ok(spawner) => spawner_code(&mut input_ast.nodes, |nodes| {
+            codegen::generate_root_code(&spawner, nodes)
+        })
+        .into(),
+        Err(err) => err.into_compile_error().into(),
+    }
+}
+
+#[proc_macro]
+pub fn btml_insert(input: TokenStream) -> TokenStream {
+    let input = match parse::replace_loop_meta(input.into()) {
+        Ok(input) => TokenStream::from(input),
+        Err(err) => return err.into_compile_error().into(),
+    };
+    let mut input_ast = parse_macro_input!(input as BtmlInput);
+
+    match entity_spawner(input_ast.spawner, SpawnerKind::Entity, "btml_insert") {
+        Ok(spawner) => spawner_code(&mut input_ast.nodes, |nodes| {
+            codegen::generate_insert_code(&spawner, nodes)
+        })
+        .into(),
+        Err(err) => err.into_compile_error().into(),
+    }
+}
+
+#[proc_macro]
+pub fn btml_patch(input: TokenStream) -> TokenStream {
+    let input = match parse::replace_loop_meta(input.into()) {
+        Ok(input) => TokenStream::from(input),
+        Err(err) => return err.into_compile_error().into(),
+    };
+    let mut input_ast = parse_macro_input!(input as BtmlPatchInput);
+
+    // The patch might be queued, so there are no ids to give back.
+    match codegen::generate_captures(&mut input_ast.nodes) {
+        Ok((declarations, _)) if declarations.is_empty() => {
+            codegen::generate_patch_code(&input_ast.target, &input_ast.root, &input_ast.nodes)
+                .into()
+        }
+        Ok(_) => syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "Capturing entities is not allowed in `btml_patch!`",
+        )
+        .into_compile_error()
+        .into(),
+        Err(err) => err.into_compile_error().into(),
+    }
+}
+
+fn entity_spawner(
+    spawner: Option<BtmlSpawnerExpr>,
+    kind: SpawnerKind,
+    name: &str,
+) -> syn::Result<BtmlSpawnerExpr> {
+    match spawner {
+        Some(mut spawner) if spawner.kind == SpawnerKind::Any => {
+            spawner.kind = kind;
+            Ok(spawner)
+        }
+        _ => Err(syn::Error::new(
+            proc_macro2::Span::call_site(),
+            format!(
+                "`{}!` expects an existing entity first, like `{}!(commands.entity(e), ...)`",
+                name, name
+            ),
+        )),
+    }
+}
+
+fn spawner_code(
+    nodes: &mut [BtmlChild],
+    generate: impl FnOnce(&[BtmlChild]) -> proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let (declarations, unwraps) = match codegen::generate_captures(nodes) {
+        Ok(captures) => captures,
+        Err(err) => return err.into_compile_error(),
+    };
+    let root_code = generate(nodes);
+
+    if declarations.is_empty() {
+        root_code
+    } else {
+        // The captured ids have to outlive the macro, so the output is a list of statements.
+        let root = syn::Ident::new("root", proc_macro2::Span::mixed_site());
+        quote! {
+            #declarations
+            let #root = #root_code;
+            #unwraps
+            #root
+        }
+    }
+}
+
+#[proc_macro_attribute]
+pub fn btml_template(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(attr as TemplateArgs);
+    let item = parse_macro_input!(item as ItemFn);
+
+    template::generate_template(args, item)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro_derive(BtmlProps, attributes(prop))]
+pub fn btml_props(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    props::generate_props(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
@@ -0,0 +1,934 @@
+use proc_macro2::{Delimiter, Group, Span, TokenStream, TokenTree};
+use quote::ToTokens;
+use syn::{
+    Block, Expr, Ident, Pat, Result, Stmt, Token, Type, braced,
+    ext::IdentExt,
+    parenthesized,
+    parse::{Parse, ParseStream, Parser},
+    punctuated::Punctuated,
+};
+
+pub struct BtmlInput {
+    pub spawner: Option<BtmlSpawnerExpr>,
+    pub nodes: Vec<BtmlChild>,
+}
+
+/// The spawner given before the markup, followed by `,` or by `=>` to spawn directly into a `World`.
+pub struct BtmlSpawnerExpr {
+    pub expr: Expr,
+    pub kind: SpawnerKind,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum SpawnerKind {
+    /// Any `BtmlSpawner`.
+    Any,
+    /// A `World`, given with `=>`.
+    World,
+    /// An existing entity, given to `btml_children!` and `btml_insert!`.
+    Entity,
+    /// An existing entity whose children are despawned first, given to `btml_replace!`.
+    Replace,
+    /// `Commands` or a `World`, given to `btml_patch!`.
+    Patch,
+}
+
+/// The input of `btml_patch!`: `Commands` or a `World`, the root entity to patch, then the markup.
+pub struct BtmlPatchInput {
+    pub target: BtmlSpawnerExpr,
+    pub root: Expr,
+    pub nodes: Vec<BtmlChild>,
+}
+
+pub enum BtmlChild {
+    Node(BtmlNode),
+    For(BtmlFor),
+    If(BtmlIf),
+    Match(BtmlMatch),
+    Let(BtmlLet),
+    While(BtmlWhile),
+    Rust(BtmlRust),
+}
+
+pub struct BtmlFor {
+    pub pat: Pat,
+    pub expr: Expr,
+    pub body: Vec<BtmlChild>,
+    pub else_branch: Option<Vec<BtmlChild>>,
+    pub meta: LoopMeta,
+}
+
+/// The loop variables (`$index`, `$first` and `$last`) used in the body of a `for` loop.
+#[derive(Default)]
+pub struct LoopMeta {
+    pub index: bool,
+    pub first: bool,
+    pub last: bool,
+}
+
+impl LoopMeta {
+    pub fn is_used(&self) -> bool {
+        self.index || self.first || self.last
+    }
+}
+
+/// An `@{ ... }` block of plain Rust, with the current spawner bound to `parent` or to the name
+/// given in `@{ |spawner| ... }`.
+pub struct BtmlRust {
+    pub binding: Option<Ident>,
+    pub stmts: Vec<Stmt>,
+}
+
+pub struct BtmlWhile {
+    pub condition: Expr,
+    pub body: Vec<BtmlChild>,
+}
+
+pub struct BtmlIf {
+    pub condition: Expr,
+    pub then_branch: Vec<BtmlChild>,
+    pub else_branch: Option<Box<BtmlElse>>,
+}
+
+pub struct BtmlMatch {
+    pub expr: Expr,
+    pub arms: Vec<BtmlArm>,
+}
+
+pub struct BtmlArm {
+    pub pat: Pat,
+    pub guard: Option<Expr>,
+    pub body: Vec<BtmlChild>,
+}
+
+pub struct BtmlLet {
+    pub pat: Pat,
+    pub ty: Option<Box<Type>>,
+    pub expr: Expr,
+    pub else_branch: Option<Vec<BtmlChild>>,
+}
+
+pub enum BtmlElse {
+    If(BtmlIf),
+    Block(Vec<BtmlChild>),
+}
+
+pub struct BtmlNode {
+    pub tag: Ident,
+    pub constructor: Option<Ident>,
+    pub attributes: Vec<BtmlAttr>,
+    pub flags: Vec<Ident>,
+    pub methods: Vec<BtmlMethod>,
+    pub base: Option<Expr>,
+    pub handlers: Vec<BtmlHandler>,
+    pub capture: Option<BtmlCapture>,
+    pub children: Vec<BtmlChild>,
+    pub content: Option<Content>,
+}
+
+/// A `#name` on a node, binding the id of the spawned entity to a variable.
+pub struct BtmlCapture {
+    pub name: Ident,
+    pub kind: CaptureKind,
+}
+
+/// How many times a captured node can be spawned, which decides the type of its variable.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CaptureKind {
+    /// `Entity`
+    Single,
+    /// `Option<Entity>`, inside a conditional branch.
+    Optional,
+    /// `Vec<Entity>`, inside a loop.
+    Many,
+}
+
+#[derive(Debug)]
+pub enum Content {
+    Arguments(Punctuated<Expr, Token![,]>),
+}
+
+#[derive(Debug)]
+pub struct BtmlAttr {
+    pub key: Ident,
+    pub value: Expr,
+}
+
+/// A `.method=value` attribute, calling a builder method on the constructed component.
+pub struct BtmlMethod {
+    pub method: Ident,
+    pub value: Option<Expr>,
+}
+
+/// An `on:event=handler` or lifecycle attribute, reacting to what happens to the spawned entity.
+pub struct BtmlHandler {
+    pub on: Ident,
+    pub event: HandlerEvent,
+    pub handler: Expr,
+}
+
+pub enum HandlerEvent {
+    /// `on_spawn`, a hook run with the commands of the entity once it's spawned.
+    Spawn,
+    /// `on_insert`, `on_remove` or `on_despawn`, observing the component of the node.
+    Lifecycle { event: Ident, component: Ident },
+    /// `on:click`, a `bevy_picking` pointer event, named in CamelCase.
+    Pointer(Ident),
+    /// `on:MyEvent`, any entity event.
+    Type(Type),
+}
+
+/// The lifecycle attributes, and the events they observe.
+const LIFECYCLE_EVENTS: &[(&str, &str)] = &[
+    ("on_insert", "Insert"),
+    ("on_remove", "Remove"),
+    ("on_despawn", "Despawn"),
+];
+
+/// The `bevy_picking` pointer events, as written after `on:`.
+const POINTER_EVENTS: &[(&str, &str)] = &[
+    ("over", "Over"),
+    ("out", "Out"),
+    ("press", "Press"),
+    ("release", "Release"),
+    ("click", "Click"),
+    ("move", "Move"),
+    ("cancel", "Cancel"),
+    ("scroll", "Scroll"),
+    ("drag_start", "DragStart"),
+    ("drag", "Drag"),
+    ("drag_end", "DragEnd"),
+    ("drag_enter", "DragEnter"),
+    ("drag_over", "DragOver"),
+    ("drag_leave", "DragLeave"),
+    ("drag_drop", "DragDrop"),
+];
+
+impl Parse for HandlerEvent {
+    fn parse(input: ParseStream) -> Result<Self> {
+        // Pointer events are lowercase, so `on:move` is read as an event and not a keyword.
+        if input.peek(Ident::peek_any) && !input.peek2(Token![::]) && !input.peek2(Token![<]) {
+            let name = input.fork().call(Ident::parse_any)?;
+            if name.to_string().starts_with(|c: char| c.is_lowercase()) {
+                let name = input.call(Ident::parse_any)?;
+                let event = POINTER_EVENTS
+                    .iter()
+                    .find(|(attr, _)| name == attr)
+                    .ok_or_else(|| {
+                        let names: Vec<_> = POINTER_EVENTS.iter().map(|(attr, _)| *attr).collect();
+                        syn::Error::new(
+                            name.span(),
+                            format!(
+                                "Unknown pointer event `{}`, expected one of: {}",
+                                name,
+                                names.join(", ")
+                            ),
+                        )
+                    })?;
+                return Ok(HandlerEvent::Pointer(Ident::new(event.1, name.span())));
+            }
+        }
+
+        Ok(HandlerEvent::Type(input.parse()?))
+    }
+}
+
+impl Parse for BtmlInput {
+    fn parse(input: ParseStream) -> Result<Self> {
+        // Markup always starts with a tag or control flow, anything else is the spawner.
+        let spawner = if input.is_empty() || input.peek(Token![<]) || peek_control_flow(input) {
+            None
+        } else {
+            let expr: Expr = input.parse()?;
+            let lookahead = input.lookahead1();
+            let kind = if lookahead.peek(Token![=>]) {
+                let _arrow: Token![=>] = input.parse()?;
+                SpawnerKind::World
+            } else if lookahead.peek(Token![,]) {
+                let _comma: Token![,] = input.parse()?;
+                SpawnerKind::Any
+            } else {
+                return Err(lookahead.error());
+            };
+            Some(BtmlSpawnerExpr { expr, kind })
+        };
+
+        let mut nodes = Vec::new();
+        while !input.is_empty() {
+            let child: BtmlChild = input.parse()?;
+            nodes.push(child);
+        }
+
+        Ok(BtmlInput { spawner, nodes })
+    }
+}
+
+impl Parse for BtmlPatchInput {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let target: Expr = input.parse()?;
+        let _comma: Token![,] = input.parse()?;
+        let span = input.span();
+        let BtmlInput { spawner, nodes } = input.parse()?;
+
+        match spawner {
+            Some(BtmlSpawnerExpr {
+                expr: root,
+                kind: SpawnerKind::Any,
+            }) => Ok(BtmlPatchInput {
+                target: BtmlSpawnerExpr {
+                    expr: target,
+                    kind: SpawnerKind::Patch,
+                },
+                root,
+                nodes,
+            }),
+            _ => Err(syn::Error::new(
+                span,
+                "Expected the entity to patch, like `btml_patch!(commands, root, ...)`",
+            )),
+        }
+    }
+}
+
+impl Parse for BtmlChild {
+    fn parse(input: ParseStream) -> Result<Self> {
+        if input.peek(Token![for]) {
+            let _for: Token![for] = input.parse()?;
+            let pat: Pat = Pat::parse_multi_with_leading_vert(input)?;
+            let _in: Token![in] = input.parse()?;
+            let expr: Expr = Expr::parse_without_eager_brace(input)?;
+
+            let content;
+            braced!(content in input);
+
+            let mut meta = LoopMeta::default();
+            scan_loop_meta(content.cursor().token_stream(), &mut meta);
+
+            let mut body = Vec::new();
+            while !content.is_empty() {
+                body.push(content.parse()?);
+            }
+
+            let else_branch = if input.peek(Token![else]) {
+                let _else: Token![else] = input.parse()?;
+
+                let content;
+                braced!(content in input);
+
+                let mut else_branch = Vec::new();
+                while !content.is_empty() {
+                    else_branch.push(content.parse()?);
+                }
+                Some(else_branch)
+            } else {
+                None
+            };
+
+            Ok(BtmlChild::For(BtmlFor {
+                pat,
+                expr,
+                body,
+                else_branch,
+                meta,
+            }))
+        } else if input.peek(Token![while]) {
+            let _while: Token![while] = input.parse()?;
+            let condition: Expr = Expr::parse_without_eager_brace(input)?;
+
+            let content;
+            braced!(content in input);
+
+            let mut body = Vec::new();
+            while !content.is_empty() {
+                body.push(content.parse()?);
+            }
+
+            Ok(BtmlChild::While(BtmlWhile { condition, body }))
+        } else if input.peek(Token![if]) {
+            let _if: Token![if] = input.parse()?;
+            let condition: Expr = Expr::parse_without_eager_brace(input)?;
+
+            let content;
+            braced!(content in input);
+
+            let mut then_branch = Vec::new();
+            while !content.is_empty() {
+                then_branch.push(content.parse()?);
+            }
+
+            let mut else_branch = None;
+            if input.peek(Token![else]) {
+                let _else: Token![else] = input.parse()?;
+                if input.peek(Token![if]) {
+                    let next_if_child: BtmlChild = input.parse()?;
+                    if let BtmlChild::If(next_if) = next_if_child {
+                        else_branch = Some(Box::new(BtmlElse::If(next_if)));
+                    } else {
+                        return Err(input.error("Expected if after else"));
+                    }
+                } else {
+                    let content;
+                    braced!(content in input);
+                    let mut else_body = Vec::new();
+                    while !content.is_empty() {
+                        else_body.push(content.parse()?);
+                    }
+                    else_branch = Some(Box::new(BtmlElse::Block(else_body)));
+                }
+            }
+
+            Ok(BtmlChild::If(BtmlIf {
+                condition,
+                then_branch,
+                else_branch,
+            }))
+        } else if input.peek(Token![match]) {
+            let _match: Token![match] = input.parse()?;
+            let expr: Expr = Expr::parse_without_eager_brace(input)?;
+
+            let arms_content;
+            braced!(arms_content in input);
+
+            let mut arms = Vec::new();
+            while !arms_content.is_empty() {
+                let pat: Pat = Pat::parse_multi_with_leading_vert(&arms_content)?;
+                let guard = if arms_content.peek(Token![if]) {
+                    let _if: Token![if] = arms_content.parse()?;
+                    Some(arms_content.parse()?)
+                } else {
+                    None
+                };
+                let _arrow: Token![=>] = arms_content.parse()?;
+
+                let content;
+                braced!(content in arms_content);
+
+                let mut body = Vec::new();
+                while !content.is_empty() {
+                    body.push(content.parse()?);
+                }
+
+                if arms_content.peek(Token![,]) {
+                    let _comma: Token![,] = arms_content.parse()?;
+                }
+
+                arms.push(BtmlArm { pat, guard, body });
+            }
+
+            Ok(BtmlChild::Match(BtmlMatch { expr, arms }))
+        } else if input.peek(Token![let]) {
+            let _let: Token![let] = input.parse()?;
+            let pat: Pat = Pat::parse_single(input)?;
+            let ty = if input.peek(Token![:]) {
+                let _colon: Token![:] = input.parse()?;
+                Some(input.parse()?)
+            } else {
+                None
+            };
+            let _eq: Token![=] = input.parse()?;
+            let expr: Expr = input.parse()?;
+
+            let else_branch = if input.peek(Token![else]) {
+                let _else: Token![else] = input.parse()?;
+
+                let content;
+                braced!(content in input);
+
+                let mut else_branch = Vec::new();
+                while !content.is_empty() {
+                    else_branch.push(content.parse()?);
+                }
+
+                if input.peek(Token![;]) {
+                    let _semi: Token![;] = input.parse()?;
+                }
+                Some(else_branch)
+            } else {
+                let _semi: Token![;] = input.parse()?;
+                None
+            };
+
+            Ok(BtmlChild::Let(BtmlLet {
+                pat,
+                ty,
+                expr,
+                else_branch,
+            }))
+        } else if input.peek(Token![@]) {
+            let _at: Token![@] = input.parse()?;
+
+            let content;
+            braced!(content in input);
+
+            let binding = if content.peek(Token![|]) {
+                let _open: Token![|] = content.parse()?;
+                let binding: Ident = content.parse()?;
+                let _close: Token![|] = content.parse()?;
+                Some(binding)
+            } else {
+                None
+            };
+            let stmts = Block::parse_within(&content)?;
+
+            Ok(BtmlChild::Rust(BtmlRust { binding, stmts }))
+        } else {
+            let node: BtmlNode = input.parse()?;
+            Ok(BtmlChild::Node(node))
+        }
+    }
+}
+
+pub fn loop_meta_ident(name: &str, span: Span) -> Ident {
+    Ident::new(&format!("__btml_{}", name), span)
+}
+
+/// Replaces `$index`, `$first` and `$last` with hidden identifiers, since `$` can't be parsed in
+/// an expression. They keep the span of the user's tokens so they resolve to the loop's bindings.
+pub fn replace_loop_meta(tokens: TokenStream) -> Result<TokenStream> {
+    let mut output = Vec::new();
+    let mut tokens = tokens.into_iter();
+
+    while let Some(token) = tokens.next() {
+        match token {
+            TokenTree::Punct(punct) if punct.as_char() == '$' => match tokens.next() {
+                Some(TokenTree::Ident(name))
+                    if name == "index" || name == "first" || name == "last" =>
+                {
+                    let ident = loop_meta_ident(&name.to_string(), name.span());
+                    output.push(TokenTree::Ident(ident));
+                }
+                _ => {
+                    return Err(syn::Error::new(
+                        punct.span(),
+                        "Unknown loop variable, expected `$index`, `$first` or `$last`",
+                    ));
+                }
+            },
+            TokenTree::Group(group) => {
+                let mut replaced =
+                    Group::new(group.delimiter(), replace_loop_meta(group.stream())?);
+                replaced.set_span(group.span());
+                output.push(TokenTree::Group(replaced));
+            }
+            token => output.push(token),
+        }
+    }
+
+    Ok(output.into_iter().collect())
+}
+
+// Loop variables inside the body of a nested `for` loop belong to that loop, so its body is skipped.
+fn scan_loop_meta(tokens: TokenStream, meta: &mut LoopMeta) {
+    let mut in_nested_loop = false;
+
+    for token in tokens {
+        match token {
+            TokenTree::Ident(ident) if ident == "for" => in_nested_loop = true,
+            TokenTree::Ident(ident) => {
+                meta.index |= ident == "__btml_index";
+                meta.first |= ident == "__btml_first";
+                meta.last |= ident == "__btml_last";
+            }
+            TokenTree::Group(group) => {
+                if in_nested_loop && group.delimiter() == Delimiter::Brace {
+                    in_nested_loop = false;
+                } else {
+                    scan_loop_meta(group.stream(), meta);
+                }
+            }
+            TokenTree::Punct(_) | TokenTree::Literal(_) => {}
+        }
+    }
+}
+
+fn peek_control_flow(input: ParseStream) -> bool {
+    input.peek(Token![for])
+        || input.peek(Token![while])
+        || input.peek(Token![if])
+        || (input.peek(Token![match]) && peek_markup_match(input))
+        || input.peek(Token![let])
+        || input.peek(Token![@])
+}
+
+// A `match` whose arms evaluate to values, like `match state { 1 => "one", _ => "other" }`, is the
+// content of its tag. Markup arms are blocks that are empty or start with a tag or control flow.
+fn peek_markup_match(input: ParseStream) -> bool {
+    let fork = input.fork();
+    let arms = (|| -> Result<TokenStream> {
+        fork.parse::<Token![match]>()?;
+        Expr::parse_without_eager_brace(&fork)?;
+        let content;
+        braced!(content in fork);
+        content.parse()
+    })();
+    let Ok(arms) = arms else {
+        return false;
+    };
+
+    let mut tokens = arms.into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        let TokenTree::Punct(punct) = &token else {
+            continue;
+        };
+        let is_arrow = punct.as_char() == '='
+            && matches!(tokens.peek(), Some(TokenTree::Punct(next)) if next.as_char() == '>');
+        if !is_arrow {
+            continue;
+        }
+        tokens.next();
+
+        return match tokens.next() {
+            Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => {
+                let body = group.stream();
+                body.is_empty() || {
+                    let parser = |body: ParseStream| {
+                        let is_markup = body.peek(Token![<]) || peek_control_flow(body);
+                        body.parse::<TokenStream>()?;
+                        Ok(is_markup)
+                    };
+                    parser.parse2(body).unwrap_or(false)
+                }
+            }
+            _ => false,
+        };
+    }
+
+    // Without arms, there's nothing to evaluate.
+    true
+}
+
+impl Parse for BtmlNode {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let _lt: Token![<] = input.parse()?;
+        let tag: Ident = input.parse()?;
+
+        let constructor = if input.peek(syn::token::Paren) {
+            let content;
+            parenthesized!(content in input);
+            Some(content.parse()?)
+        } else {
+            None
+        };
+
+        let mut attributes = Vec::new();
+        let mut flags = Vec::new();
+        let mut methods = Vec::new();
+        let mut base = None;
+        let mut handlers = Vec::new();
+        let mut capture = None;
+
+        while !input.peek(Token![>]) && !input.peek(Token![/]) {
+            if input.peek(Token![#]) {
+                let _pound: Token![#] = input.parse()?;
+                let name: Ident = input.parse()?;
+                if capture.is_some() {
+                    return Err(syn::Error::new(
+                        name.span(),
+                        "A node can only capture its entity once",
+                    ));
+                }
+                capture = Some(BtmlCapture {
+                    name,
+                    kind: CaptureKind::Single,
+                });
+
+                if input.peek(Token![,]) {
+                    let _comma: Token![,] = input.parse()?;
+                }
+                continue;
+            }
+
+            if input.peek(Token![..]) {
+                let dots: Token![..] = input.parse()?;
+                if base.is_some() {
+                    return Err(syn::Error::new_spanned(
+                        dots,
+                        "A component can only be spread from one base",
+                    ));
+                }
+                base = Some(parse_spread(input)?);
+
+                if input.peek(Token![,]) {
+                    let _comma: Token![,] = input.parse()?;
+                }
+                continue;
+            }
+
+            // Without a value, the method is called without arguments, like `.flipped`.
+            if input.peek(Token![.]) {
+                let _dot: Token![.] = input.parse()?;
+                let method: Ident = input.parse()?;
+                let value = if input.peek(Token![=]) {
+                    let _eq: Token![=] = input.parse()?;
+                    Some(parse_attr_value(input)?)
+                } else {
+                    None
+                };
+                methods.push(BtmlMethod { method, value });
+
+                if input.peek(Token![,]) {
+                    let _comma: Token![,] = input.parse()?;
+                }
+                continue;
+            }
+
+            let key: Ident = input.parse()?;
+            let lifecycle = LIFECYCLE_EVENTS.iter().find(|(attr, _)| key == attr);
+
+            if key == "on" && input.peek(Token![:]) && !input.peek(Token![::]) {
+                let _colon: Token![:] = input.parse()?;
+                let event: HandlerEvent = input.parse()?;
+                let _eq: Token![=] = input.parse()?;
+                let handler = parse_attr_value(input)?;
+                handlers.push(BtmlHandler {
+                    on: key,
+                    event,
+                    handler,
+                });
+            } else if (key == "on_spawn" || lifecycle.is_some()) && input.peek(Token![=]) {
+                let _eq: Token![=] = input.parse()?;
+                let handler = parse_attr_value(input)?;
+                let event = match lifecycle {
+                    Some(_) if tag == "entity" => {
+                        return Err(syn::Error::new(
+                            key.span(),
+                            format!(
+                                "`{}` observes the component of its node, put it on a component",
+                                key
+                            ),
+                        ));
+                    }
+                    Some((_, event)) => HandlerEvent::Lifecycle {
+                        event: Ident::new(event, key.span()),
+                        component: tag.clone(),
+                    },
+                    None => HandlerEvent::Spawn,
+                };
+                handlers.push(BtmlHandler {
+                    on: key,
+                    event,
+                    handler,
+                });
+            } else if input.peek(Token![=]) {
+                let _eq: Token![=] = input.parse()?;
+                let value = parse_attr_value(input)?;
+                attributes.push(BtmlAttr { key, value });
+            } else {
+                flags.push(key);
+            }
+
+            if input.peek(Token![,]) {
+                let _comma: Token![,] = input.parse()?;
+            }
+        }
+
+        let mut is_self_closing = false;
+        if input.peek(Token![/]) {
+            let _slash: Token![/] = input.parse()?;
+            let _gt: Token![>] = input.parse()?;
+            is_self_closing = true;
+        } else {
+            let _gt: Token![>] = input.parse()?;
+        }
+
+        let mut children = Vec::new();
+        let mut content = None;
+
+        if !is_self_closing {
+            if !input.peek(Token![<]) && !peek_control_flow(input) {
+                let mut tokens = proc_macro2::TokenStream::new();
+
+                while !input.peek(Token![<]) && !peek_control_flow(input) && !input.is_empty() {
+                    let tt: TokenTree = input.parse()?;
+                    tokens.extend(std::iter::once(tt));
+                }
+
+                if !tokens.is_empty() {
+                    let parser = Punctuated::<Expr, Token![,]>::parse_terminated;
+                    match parser.parse2(tokens) {
+                        Ok(args) => {
+                            content = Some(Content::Arguments(args));
+                        }
+                        Err(e) => return Err(e),
+                    }
+                }
+            }
+
+            while (input.peek(Token![<]) && !input.peek2(Token![/])) || peek_control_flow(input) {
+                let child: BtmlChild = input.parse()?;
+                children.push(child);
+            }
+
+            let _lt: Token![<] = input.parse()?;
+            let _slash: Token![/] = input.parse()?;
+            let closing: Ident = input.parse()?;
+
+            if closing != tag {
+                return Err(syn::Error::new(
+                    closing.span(),
+                    format!("Expected closing tag </{}>, found </{}>", tag, closing),
+                ));
+            }
+            let _gt: Token![>] = input.parse()?;
+        }
+
+        if tag == "entity"
+            && (constructor.is_some()
+                || attributes.iter().any(|a| a.key != "key")
+                || !methods.is_empty()
+                || content.is_some())
+        {
+            return Err(syn::Error::new(
+                tag.span(),
+                "`<entity>` only groups the nodes inside it into one entity and takes no attributes or content other than a `key`",
+            ));
+        }
+
+        if let Some(base) = &base {
+            let flag = flags.iter().find(|f| *f == "default" || *f == "no_default");
+            if tag == "entity"
+                || tag
+                    .to_string()
+                    .starts_with(|c: char| c.is_ascii_lowercase())
+            {
+                return Err(syn::Error::new_spanned(
+                    base,
+                    "Only components can be spread from a base value",
+                ));
+            } else if constructor.is_some() || content.is_some() {
+                return Err(syn::Error::new_spanned(
+                    base,
+                    "A base value fills the remaining fields of a struct, it can't be used with a constructor or content",
+                ));
+            } else if let Some(flag) = flag {
+                return Err(syn::Error::new(
+                    flag.span(),
+                    format!(
+                        "`{}` can't be used with a base value, which gives the remaining fields",
+                        flag
+                    ),
+                ));
+            }
+        }
+
+        if let Some(method) = methods.first()
+            && tag != "entity"
+            && tag
+                .to_string()
+                .starts_with(|c: char| c.is_ascii_lowercase())
+        {
+            return Err(syn::Error::new(
+                method.method.span(),
+                "Method calls build a component, templates and other lowercase tags only take attributes",
+            ));
+        }
+
+        if let Some(handler) = handlers.first()
+            && tag != "entity"
+            && tag
+                .to_string()
+                .starts_with(|c: char| c.is_ascii_lowercase())
+        {
+            return Err(syn::Error::new(
+                handler.on.span(),
+                "Event handlers react to a spawned entity, put them on a component or an `<entity>`",
+            ));
+        }
+
+        Ok(BtmlNode {
+            tag,
+            constructor,
+            attributes,
+            flags,
+            methods,
+            base,
+            handlers,
+            capture,
+            children,
+            content,
+        })
+    }
+}
+
+/// Parses the base of a `..base` spread, which also ends before the next attribute since it's
+/// usually a path, a field or a call, as in `<Node ..base width=...>`.
+fn parse_spread(input: ParseStream) -> Result<Expr> {
+    let mut tokens = TokenStream::new();
+    let mut continued = true;
+
+    while !input.is_empty() {
+        let next_attribute = input.peek(Ident)
+            || input.peek(Token![#])
+            || input.peek(Token![..])
+            || (input.peek(Token![.]) && input.peek2(Ident) && input.peek3(Token![=]));
+        if input.peek(Token![,])
+            || input.peek(Token![>])
+            || input.peek(Token![/])
+            || (!continued && next_attribute)
+        {
+            break;
+        }
+
+        let tt: TokenTree = input.parse()?;
+        // An identifier right after `.`, `::`, `&` or `*` is still part of the base.
+        continued = matches!(
+            &tt,
+            TokenTree::Punct(punct) if matches!(punct.as_char(), '.' | ':' | '&' | '*')
+        );
+        tokens.extend(std::iter::once(tt));
+    }
+
+    if tokens.is_empty() {
+        return Err(input.error("Expected a base value after `..`"));
+    }
+    syn::parse2(tokens)
+}
+
+/// Parses an attribute value, which ends at the next `,`, `>` or `/`.
+fn parse_attr_value(input: ParseStream) -> Result<Expr> {
+    let mut tokens = TokenStream::new();
+
+    // The parameters of a closure can have types like `On<Pointer<Click>>`, so they're parsed
+    // before looking for the end of the value.
+    if input.peek(Token![move]) || input.peek(Token![|]) {
+        if input.peek(Token![move]) {
+            input.parse::<Token![move]>()?.to_tokens(&mut tokens);
+        }
+        if input.peek(Token![||]) {
+            input.parse::<Token![||]>()?.to_tokens(&mut tokens);
+        } else {
+            input.parse::<Token![|]>()?.to_tokens(&mut tokens);
+            while !input.peek(Token![|]) {
+                Pat::parse_single(input)?.to_tokens(&mut tokens);
+                if input.peek(Token![:]) {
+                    input.parse::<Token![:]>()?.to_tokens(&mut tokens);
+                    input.parse::<Type>()?.to_tokens(&mut tokens);
+                }
+                if !input.peek(Token![,]) {
+                    break;
+                }
+                input.parse::<Token![,]>()?.to_tokens(&mut tokens);
+            }
+            input.parse::<Token![|]>()?.to_tokens(&mut tokens);
+        }
+        if input.peek(Token![->]) {
+            input.parse::<Token![->]>()?.to_tokens(&mut tokens);
+            input.parse::<Type>()?.to_tokens(&mut tokens);
+            input.parse::<Block>()?.to_tokens(&mut tokens);
+            return syn::parse2(tokens);
+        }
+    }
+
+    while !input.is_empty() {
+        if input.peek(Token![,]) || input.peek(Token![>]) || input.peek(Token![/]) {
+            break;
+        }
+        let tt: TokenTree = input.parse()?;
+        tokens.extend(std::iter::once(tt));
+    }
+
+    if tokens.is_empty() {
+        return Err(input.error("Expected attribute value"));
+    }
+    syn::parse2(tokens)
+}
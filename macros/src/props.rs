@@ -0,0 +1,289 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{
+    Data, DeriveInput, Expr, Fields, GenericArgument, GenericParam, Ident, PathArguments, Result,
+    Type,
+};
+
+enum PropKind {
+    Required,
+    Default(Expr),
+    Optional,
+}
+
+struct Prop {
+    ident: Ident,
+    ty: Type,
+    kind: PropKind,
+}
+
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first() {
+        Some(GenericArgument::Type(inner)) if args.args.len() == 1 => Some(inner),
+        _ => None,
+    }
+}
+
+fn parse_prop(field: &syn::Field) -> Result<Prop> {
+    let ident = field.ident.clone().unwrap();
+    let mut kind = if option_inner(&field.ty).is_some() {
+        PropKind::Optional
+    } else {
+        PropKind::Required
+    };
+
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("prop")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("default") {
+                kind = if meta.input.peek(syn::Token![=]) {
+                    PropKind::Default(meta.value()?.parse()?)
+                } else {
+                    PropKind::Default(syn::parse_quote! { Default::default() })
+                };
+                Ok(())
+            } else {
+                Err(meta.error("Unknown prop attribute, expected `default` or `default = ...`"))
+            }
+        })?;
+    }
+
+    Ok(Prop {
+        ident,
+        ty: field.ty.clone(),
+        kind,
+    })
+}
+
+/// Generates `Props::builder()`, where required props are tracked in the builder type so that
+/// `build()` fails to compile until all of them are set.
+pub fn generate_props(input: &DeriveInput) -> Result<TokenStream> {
+    let name = &input.ident;
+    let vis = &input.vis;
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new(
+                    name.span(),
+                    "BtmlProps can only be derived for structs with named fields",
+                ));
+            }
+        },
+        _ => {
+            return Err(syn::Error::new(
+                name.span(),
+                "BtmlProps can only be derived for structs",
+            ));
+        }
+    };
+
+    let props = fields.iter().map(parse_prop).collect::<Result<Vec<_>>>()?;
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let generic_args: Vec<TokenStream> = input
+        .generics
+        .params
+        .iter()
+        .map(|param| match param {
+            GenericParam::Lifetime(l) => {
+                let lifetime = &l.lifetime;
+                quote! { #lifetime }
+            }
+            GenericParam::Type(t) => {
+                let ident = &t.ident;
+                quote! { #ident }
+            }
+            GenericParam::Const(c) => {
+                let ident = &c.ident;
+                quote! { #ident }
+            }
+        })
+        .collect();
+    let generic_params: Vec<GenericParam> = input
+        .generics
+        .params
+        .iter()
+        .cloned()
+        .map(|mut param| {
+            if let GenericParam::Type(t) = &mut param {
+                t.eq_token = None;
+                t.default = None;
+            }
+            if let GenericParam::Const(c) = &mut param {
+                c.eq_token = None;
+                c.default = None;
+            }
+            param
+        })
+        .collect();
+    let predicates = where_clause.map(|w| &w.predicates);
+
+    let builder = Ident::new("__BtmlPropsBuilder", Span::mixed_site());
+    let required: Vec<&Prop> = props
+        .iter()
+        .filter(|p| matches!(p.kind, PropKind::Required))
+        .collect();
+    let states: Vec<Ident> = (0..required.len())
+        .map(|i| format_ident!("__Prop{}", i))
+        .collect();
+
+    let builder_fields = props.iter().map(|prop| {
+        let ident = &prop.ident;
+        let ty = &prop.ty;
+        match prop.kind {
+            PropKind::Required => {
+                let index = required.iter().position(|r| r.ident == prop.ident).unwrap();
+                let state = &states[index];
+                quote! { #ident: #state }
+            }
+            PropKind::Default(_) => quote! { #ident: Option<#ty> },
+            PropKind::Optional => quote! { #ident: #ty },
+        }
+    });
+
+    let setters = props.iter().map(|prop| {
+        let ident = &prop.ident;
+        let ty = &prop.ty;
+        match prop.kind {
+            PropKind::Required => {
+                let index = required.iter().position(|r| r.ident == prop.ident).unwrap();
+                let next_states = states.iter().enumerate().map(|(i, state)| {
+                    if i == index {
+                        quote! { (#ty,) }
+                    } else {
+                        quote! { #state }
+                    }
+                });
+                let moved = props.iter().map(|other| {
+                    let other_ident = &other.ident;
+                    if other.ident == prop.ident {
+                        quote! { #other_ident: (#ident,) }
+                    } else {
+                        quote! { #other_ident: self.#other_ident }
+                    }
+                });
+                quote! {
+                    #vis fn #ident(self, #ident: #ty) -> #builder<#( #generic_args, )* #( #next_states ),*> {
+                        #builder {
+                            #( #moved, )*
+                            __marker: ::core::marker::PhantomData,
+                        }
+                    }
+                }
+            }
+            PropKind::Default(_) => quote! {
+                #vis fn #ident(mut self, #ident: #ty) -> Self {
+                    self.#ident = Some(#ident);
+                    self
+                }
+            },
+            PropKind::Optional => {
+                let inner = option_inner(ty).unwrap();
+                quote! {
+                    #vis fn #ident(mut self, #ident: impl Into<Option<#inner>>) -> Self {
+                        self.#ident = #ident.into();
+                        self
+                    }
+                }
+            }
+        }
+    });
+
+    let required_traits = required.iter().map(|prop| {
+        let ident = &prop.ident;
+        let message = format!("missing required prop `{}` for `{}`", ident, name);
+        let label = format!("`{}` must be set", ident);
+        let required_trait = format_ident!("__BtmlRequired_{}", ident);
+        quote! {
+            #[diagnostic::on_unimplemented(message = #message, label = #label)]
+            #[allow(non_camel_case_types)]
+            #vis trait #required_trait<T> {
+                fn into_prop(self) -> T;
+            }
+
+            impl<T> #required_trait<T> for (T,) {
+                fn into_prop(self) -> T {
+                    self.0
+                }
+            }
+        }
+    });
+
+    let required_bounds = required.iter().zip(&states).map(|(prop, state)| {
+        let ty = &prop.ty;
+        let required_trait = format_ident!("__BtmlRequired_{}", prop.ident);
+        quote! { #state: #required_trait<#ty> }
+    });
+
+    let build_fields = props.iter().map(|prop| {
+        let ident = &prop.ident;
+        match &prop.kind {
+            PropKind::Required => {
+                let required_trait = format_ident!("__BtmlRequired_{}", ident);
+                quote! { #ident: #required_trait::into_prop(self.#ident) }
+            }
+            PropKind::Default(default) => quote! {
+                #ident: match self.#ident {
+                    Some(value) => value,
+                    None => #default,
+                }
+            },
+            PropKind::Optional => quote! { #ident: self.#ident },
+        }
+    });
+
+    let empty_fields = props.iter().map(|prop| {
+        let ident = &prop.ident;
+        match prop.kind {
+            PropKind::Required => quote! { #ident: () },
+            PropKind::Default(_) | PropKind::Optional => quote! { #ident: None },
+        }
+    });
+    let empty_states = states.iter().map(|_| quote! { () });
+
+    Ok(quote! {
+        const _: () = {
+            #( #required_traits )*
+
+            #[doc(hidden)]
+            #vis struct #builder<#( #generic_params, )* #( #states ),*> #where_clause {
+                #( #builder_fields, )*
+                __marker: ::core::marker::PhantomData<fn() -> #name #ty_generics>,
+            }
+
+            impl #impl_generics #name #ty_generics #where_clause {
+                #vis fn builder() -> #builder<#( #generic_args, )* #( #empty_states ),*> {
+                    #builder {
+                        #( #empty_fields, )*
+                        __marker: ::core::marker::PhantomData,
+                    }
+                }
+            }
+
+            impl<#( #generic_params, )* #( #states ),*> #builder<#( #generic_args, )* #( #states ),*> #where_clause {
+                #( #setters )*
+
+                #vis fn build(self) -> #name #ty_generics
+                where
+                    #( #required_bounds, )*
+                    #predicates
+                {
+                    #name {
+                        #( #build_fields, )*
+                    }
+                }
+            }
+        };
+    })
+}
@@ -0,0 +1,389 @@
+use proc_macro2::{Span, TokenStream, TokenTree};
+use quote::{format_ident, quote};
+use syn::{
+    Attribute, Block, Data, DeriveInput, FnArg, GenericParam, Generics, Ident, ItemFn, Lifetime,
+    LifetimeParam, Pat, PatType, Result, Signature, Token, Type, Visibility, parenthesized,
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    visit_mut::{self, VisitMut},
+};
+
+pub struct TemplateArgs {
+    pub slots: Vec<Ident>,
+}
+
+impl Parse for TemplateArgs {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut slots = Vec::new();
+
+        if !input.is_empty() {
+            let key: Ident = input.parse()?;
+            if key != "slots" {
+                return Err(syn::Error::new(
+                    key.span(),
+                    format!("Unknown template argument `{}`, expected `slots(...)`", key),
+                ));
+            }
+
+            let content;
+            parenthesized!(content in input);
+            slots.extend(Punctuated::<Ident, Token![,]>::parse_terminated(&content)?);
+        }
+
+        Ok(TemplateArgs { slots })
+    }
+}
+
+/// Gives every elided reference lifetime of a prop type a name, so it can be stored in the props struct.
+struct NameElidedLifetimes {
+    lifetime: Lifetime,
+    used: bool,
+}
+
+impl VisitMut for NameElidedLifetimes {
+    fn visit_type_reference_mut(&mut self, reference: &mut syn::TypeReference) {
+        if reference.lifetime.is_none() {
+            reference.lifetime = Some(self.lifetime.clone());
+            self.used = true;
+        }
+        visit_mut::visit_type_reference_mut(self, reference);
+    }
+
+    fn visit_lifetime_mut(&mut self, lifetime: &mut Lifetime) {
+        if lifetime.ident == "_" {
+            *lifetime = self.lifetime.clone();
+            self.used = true;
+        }
+    }
+
+    fn visit_type_bare_fn_mut(&mut self, _: &mut syn::TypeBareFn) {}
+
+    fn visit_parenthesized_generic_arguments_mut(
+        &mut self,
+        _: &mut syn::ParenthesizedGenericArguments,
+    ) {
+    }
+
+    fn visit_type_impl_trait_mut(&mut self, _: &mut syn::TypeImplTrait) {}
+}
+
+pub fn generate_template(args: TemplateArgs, item: ItemFn) -> Result<TokenStream> {
+    let ItemFn {
+        attrs,
+        vis,
+        mut sig,
+        block,
+    } = item;
+    let name = sig.ident.clone();
+
+    if name == "children"
+        || name == "slot"
+        || name == "entity"
+        || !name
+            .to_string()
+            .starts_with(|c: char| c.is_ascii_lowercase())
+    {
+        return Err(syn::Error::new(
+            name.span(),
+            "A template name must start with a lowercase letter and cannot be `children`, `slot` or `entity`",
+        ));
+    }
+
+    let mut inputs = std::mem::take(&mut sig.inputs).into_iter();
+    let mut spawner = match inputs.next() {
+        Some(FnArg::Typed(spawner)) => spawner,
+        _ => {
+            return Err(syn::Error::new(
+                sig.paren_token.span.join(),
+                "A template takes the spawner as its first argument, e.g. `parent: &mut ChildSpawnerCommands`",
+            ));
+        }
+    };
+
+    let mut lifetimes = NameElidedLifetimes {
+        lifetime: Lifetime::new("'btml", Span::call_site()),
+        used: false,
+    };
+    let props: Vec<PatType> = inputs
+        .map(|input| match input {
+            FnArg::Typed(prop) => Ok(prop),
+            FnArg::Receiver(receiver) => Err(syn::Error::new_spanned(
+                receiver,
+                "A template cannot take `self`",
+            )),
+        })
+        .collect::<Result<_>>()?;
+
+    if let Some(props_struct) = props
+        .iter()
+        .find(|p| p.attrs.iter().any(|a| a.path().is_ident("props")))
+    {
+        if props.len() > 1 || !args.slots.is_empty() {
+            return Err(syn::Error::new_spanned(
+                props_struct,
+                "A template taking a `#[props]` struct cannot have other props or slots",
+            ));
+        }
+        return generate_props_struct_template(
+            attrs,
+            vis,
+            sig,
+            block,
+            spawner,
+            props_struct.clone(),
+        );
+    }
+
+    let spawner_generic = spawner_generic(&mut sig.generics, &mut spawner.ty);
+
+    let mut fields = Vec::new();
+    let mut bindings = Vec::new();
+
+    for mut prop in props {
+        let Pat::Ident(pat) = &*prop.pat else {
+            return Err(syn::Error::new_spanned(
+                &prop.pat,
+                "Template props must be plain identifiers",
+            ));
+        };
+        if let Type::ImplTrait(_) = &*prop.ty {
+            return Err(syn::Error::new_spanned(
+                &prop.ty,
+                "Template props cannot use `impl Trait`, use a generic parameter instead",
+            ));
+        }
+
+        lifetimes.visit_type_mut(&mut prop.ty);
+
+        let prop_attrs = prop.attrs.iter().filter(|a| a.path().is_ident("prop"));
+        let ident = &pat.ident;
+        let ty = &prop.ty;
+        fields.push(quote! { #( #prop_attrs )* #vis #ident: #ty });
+        bindings.push(pat.clone());
+    }
+
+    if !args.slots.is_empty() {
+        lifetimes.used = true;
+    }
+
+    if lifetimes.used {
+        sig.generics.params.insert(
+            0,
+            GenericParam::Lifetime(LifetimeParam::new(lifetimes.lifetime.clone())),
+        );
+    }
+
+    // A generic spawner only belongs to the props when the slots store closures taking it.
+    let mut props_generics = sig.generics.clone();
+    let spawner_in_props = spawner_generic.as_ref().is_some_and(|generic| {
+        !args.slots.is_empty()
+            || fields
+                .iter()
+                .any(|field| mentions_ident(field.clone(), generic))
+    });
+    if let Some(generic) = spawner_generic.as_ref().filter(|_| !spawner_in_props) {
+        props_generics.params = std::mem::take(&mut props_generics.params)
+            .into_iter()
+            .filter(|param| !matches!(param, GenericParam::Type(t) if t.ident == *generic))
+            .collect();
+        if let Some(where_clause) = &mut props_generics.where_clause {
+            where_clause.predicates = std::mem::take(&mut where_clause.predicates)
+                .into_iter()
+                .filter(|predicate| !mentions_ident(quote! { #predicate }, generic))
+                .collect();
+        }
+    }
+
+    let (impl_generics, ty_generics, where_clause) = props_generics.split_for_impl();
+    let props = Ident::new("props", Span::mixed_site());
+
+    // Slots live in their own struct so callers only have to fill the ones they use.
+    let slots_name = format_ident!("__btml_{}_slots", name);
+    let slot_lifetime = &lifetimes.lifetime;
+    let slot_names = &args.slots;
+    // Slots of a generic spawner are placed in `<children>`, where every level has the same
+    // child spawner type.
+    let spawner_ty = match &spawner_generic {
+        Some(generic) => {
+            quote! { &mut <#generic as ::bevy_btml::BtmlSpawner>::ChildSpawner<'_> }
+        }
+        None => {
+            let ty = &spawner.ty;
+            quote! { #ty }
+        }
+    };
+    // Markup passes its spawner to the slot setters, which gives a generic spawner its type.
+    let spawner_target = match &spawner_generic {
+        Some(generic) => quote! { #generic },
+        None => quote! { impl ?Sized },
+    };
+
+    let (slots_struct, slots_ty) = if slot_names.is_empty() {
+        (quote! {}, quote! { () })
+    } else {
+        let generic = spawner_generic.iter();
+        let bounded_generic = generic.clone();
+        let slots_params =
+            quote! { <#slot_lifetime #( , #bounded_generic: ::bevy_btml::BtmlSpawner )*> };
+        let slots_generics = quote! { <#slot_lifetime #( , #generic )*> };
+        // Deriving `Default` would require the spawner to implement it.
+        let slots_struct = quote! {
+            #[doc(hidden)]
+            #[allow(non_camel_case_types)]
+            #vis struct #slots_name #slots_params {
+                #( #vis #slot_names: Option<Box<dyn FnOnce(#spawner_ty) + #slot_lifetime>> ),*
+            }
+
+            impl #slots_params Default for #slots_name #slots_generics {
+                fn default() -> Self {
+                    Self {
+                        #( #slot_names: None ),*
+                    }
+                }
+            }
+        };
+        (slots_struct, quote! { #slots_name #slots_generics })
+    };
+
+    let props_input: DeriveInput = syn::parse_quote! {
+        #vis struct #name #impl_generics #where_clause {
+            #( #fields, )*
+            #[prop(default)]
+            #vis __slots: #slots_ty,
+        }
+    };
+    let props_builder = crate::props::generate_props(&props_input)?;
+
+    let mut props_struct = props_input;
+    if let Data::Struct(data) = &mut props_struct.data {
+        for field in data.fields.iter_mut() {
+            field.attrs.retain(|a| !a.path().is_ident("prop"));
+        }
+    }
+
+    sig.inputs.push(FnArg::Typed(spawner));
+    sig.inputs
+        .push(syn::parse_quote! { #props: #name #ty_generics });
+
+    Ok(quote! {
+        #slots_struct
+
+        #[doc(hidden)]
+        #[allow(non_camel_case_types)]
+        #props_struct
+
+        #props_builder
+
+        impl #impl_generics #name #ty_generics #where_clause {
+            #(
+                #vis fn #slot_names(
+                    mut self,
+                    _spawner: &#spawner_target,
+                    content: impl FnOnce(#spawner_ty) + #slot_lifetime,
+                ) -> Self {
+                    self.__slots.#slot_names = Some(Box::new(content));
+                    self
+                }
+            )*
+        }
+
+        #( #attrs )*
+        #vis #sig {
+            let #name { #( #bindings, )* __slots } = #props;
+            #block
+        }
+    })
+}
+
+/// Turns a `&mut impl BtmlSpawner` spawner into a named generic, and returns the generic the
+/// spawner is a reference to, if any.
+fn spawner_generic(generics: &mut Generics, ty: &mut Type) -> Option<Ident> {
+    let Type::Reference(reference) = ty else {
+        return None;
+    };
+
+    match &*reference.elem {
+        Type::ImplTrait(impl_trait) => {
+            let generic = Ident::new("__BtmlSpawner", Span::call_site());
+            let bounds = &impl_trait.bounds;
+            generics
+                .params
+                .push(syn::parse_quote! { #generic: #bounds });
+            *reference.elem = syn::parse_quote! { #generic };
+            Some(generic)
+        }
+        Type::Path(path) if path.qself.is_none() => {
+            let ident = path.path.get_ident()?;
+            generics
+                .type_params()
+                .find_map(|param| (param.ident == *ident).then(|| param.ident.clone()))
+        }
+        _ => None,
+    }
+}
+
+fn mentions_ident(tokens: TokenStream, ident: &Ident) -> bool {
+    tokens.into_iter().any(|token| match token {
+        TokenTree::Ident(other) => other == *ident,
+        TokenTree::Group(group) => mentions_ident(group.stream(), ident),
+        _ => false,
+    })
+}
+
+// The template name becomes an alias of the user's props struct, so markup can call its builder.
+fn generate_props_struct_template(
+    attrs: Vec<Attribute>,
+    vis: Visibility,
+    mut sig: Signature,
+    block: Box<Block>,
+    spawner: PatType,
+    mut props: PatType,
+) -> Result<TokenStream> {
+    let name = &sig.ident;
+    props.attrs.retain(|a| !a.path().is_ident("props"));
+
+    let mut lifetimes = NameElidedLifetimes {
+        lifetime: Lifetime::new("'btml", Span::call_site()),
+        used: false,
+    };
+    let mut props_ty = (*props.ty).clone();
+    lifetimes.visit_type_mut(&mut props_ty);
+
+    let mut alias_params: Vec<TokenStream> = Vec::new();
+    if lifetimes.used {
+        let lifetime = &lifetimes.lifetime;
+        alias_params.push(quote! { #lifetime });
+    }
+    for param in &sig.generics.params {
+        alias_params.push(match param {
+            GenericParam::Lifetime(l) => {
+                let lifetime = &l.lifetime;
+                quote! { #lifetime }
+            }
+            GenericParam::Type(t) => {
+                let ident = &t.ident;
+                quote! { #ident }
+            }
+            GenericParam::Const(c) => {
+                let ident = &c.ident;
+                let ty = &c.ty;
+                quote! { const #ident: #ty }
+            }
+        });
+    }
+
+    sig.inputs.push(FnArg::Typed(spawner));
+    sig.inputs.push(FnArg::Typed(props));
+
+    Ok(quote! {
+        #[doc(hidden)]
+        #[allow(non_camel_case_types)]
+        #vis type #name<#( #alias_params ),*> = #props_ty;
+
+        #( #attrs )*
+        #vis #sig {
+            let __slots = ();
+            #block
+        }
+    })
+}
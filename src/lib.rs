@@ -19,6 +19,7 @@
 //!   - `<Tag />`: Generates `Tag` as a value. Use this for Unit Structs (marker components) that don't need initialization.
 //!   - `<Tag default />`: Generates `Tag::default()`. Use this for Structs with fields where you want the default values.
 //!   - `<Tag attr=val />`: Generates `Tag { attr: val, ..Default::default() }`.
+//!   - `<Tag ..base attr=val />`: Generates `Tag { attr: val, ..base.clone() }`, to share a style between components.
 //!   - `<Tag flag, attr=val />`: Attributes without values (flags) are ignored during struct initialization but are used during macro logic (like `default` and `no_default` that tells the macro how to generate the component).
 //!
 //! - **Tuple Structs**: Initialize tuple structs (like `BackgroundColor` or `TextColor`) by providing the value as content.
@@ -26,6 +27,14 @@
 //!   <BackgroundColor>Color::BLACK</BackgroundColor>
 //!   ```
 //!
+//! - **Builder Methods**: Call methods on the constructed component with `.method=value` attributes, or `.method` for
+//!   methods without arguments. They work with constructors, tuple structs and field attributes alike.
+//!   ```rust
+//!   <TextLayout default .with_justify=Justify::Center, .with_no_wrap />
+//!   <Transform(from_xyz) .with_scale=Vec3::splat(2.0)>0.0, 8.0, 4.0</Transform>
+//!   ```
+//!   Note: `.method=value` generates `.method(value)` after the component, so each method takes a single argument.
+//!
 //! - **Hierarchy**: Use the `<children>` tag to nest entities. Nested node without the `<children>` tag are treated as sibling components of the same entity, even if they are nested.
 //!   ```rust
 //!   btml!(commands,
@@ -54,6 +63,42 @@
 //!   ```
 //!   Note: All entities spawned within the loop are separate children of the parent entity. The loop itself does not create an intermediate wrapper entity.
 //!
+//!   The body of a `for` loop can use `$index`, `$first` and `$last`, which refer to the innermost loop.
+//!   Here each row is wrapped in `<children>`, so the body has no components of its own and the separators are spawned
+//!   between the rows instead of inside them.
+//!   ```rust
+//!   <children>
+//!       for item in items {
+//!           <children>
+//!               <Text(new)>format!("{}. {}", $index + 1, item)</Text>
+//!           </children>
+//!           if !$last {
+//!               <Separator />
+//!           }
+//!       }
+//!   </children>
+//!   ```
+//!
+//!   A `for` loop can have an `else` block, spawned only when the iterator yields nothing.
+//!   ```rust
+//!   <children>
+//!       for friend in friends {
+//!           <Text(new)>friend.name</Text>
+//!       } else {
+//!           <Text(new)>"No friends online"</Text>
+//!       }
+//!   </children>
+//!   ```
+//!
+//!   `while` and `while let` loops work the same way, for sources that don't implement `IntoIterator`.
+//!   ```rust
+//!   <children>
+//!       while let Some(entry) = cursor.next_entry() {
+//!           <Text(new)>entry.label</Text>
+//!       }
+//!   </children>
+//!   ```
+//!
 //! - **Conditional Rendering**: Use Rust `if` and `else` blocks to conditionally spawn entities.
 //!   ```rust
 //!   <children>
@@ -64,6 +109,321 @@
 //!       }
 //!   </children>
 //!   ```
+//!   Patterns work too, with `if let`, `else if let` and `let ... else`. The bindings are usable in the nested markup.
+//!   ```rust
+//!   <children>
+//!       if let Some(item) = selected_item {
+//!           <Text(new)>item.name.clone()</Text>
+//!       } else if let Some(hint) = hint {
+//!           <Text(new)>hint</Text>
+//!       }
+//!   </children>
+//!   <children>
+//!       let Some(quest) = current_quest else {
+//!           <Text(new)>"No active quest"</Text>
+//!       };
+//!       <Text(new)>quest.title.clone()</Text>
+//!   </children>
+//!   ```
+//!   Note: When a `let ... else` pattern doesn't match, its `else` markup is spawned instead of the rest of the block.
+//!   The markup before the `let ... else` is always spawned.
+//!
+//! - **Let Bindings**: Compute values next to where they're used with `let` statements between nodes.
+//!   ```rust
+//!   <children>
+//!       let label = format!("{} HP", hp);
+//!       <Text(new)>label</Text>
+//!   </children>
+//!   ```
+//!   Note: Bindings are evaluated in source order, like Rust statements. A binding is scoped to its enclosing block and
+//!   visible to the siblings after it and their descendants, and a later `let` can shadow it.
+//!
+//! - **Pattern Matching**: Use Rust `match` expressions, with guards and multiple patterns, to pick which entities to spawn.
+//!   ```rust
+//!   <children>
+//!       match state {
+//!           MenuState::Main | MenuState::Paused => {
+//!               <Text(new)>"Main Menu"</Text>
+//!           }
+//!           MenuState::Level(level) if level > 10 => {
+//!               <Text(new)>"Endgame"</Text>
+//!           }
+//!           _ => {}
+//!       }
+//!   </children>
+//!   ```
+//!   Note: A `match` whose arms are values instead of markup blocks is the content of its tag, like in
+//!   `<Text(new)>match count { 1 => "one", _ => "many" }</Text>`.
+//!
+//! - **Rust Blocks**: Run arbitrary Rust in the middle of a tree with `@{ ... }`. The current spawner is
+//!   available as `parent`, or under the name given with `@{ |spawner| ... }`.
+//!   ```rust
+//!   <children>
+//!       <Text(new)>"Map"</Text>
+//!       @{ spawn_minimap(parent, &world_map); }
+//!   </children>
+//!   ```
+//!
+//! - **Spawners**: The spawner can be any expression whose type implements [`BtmlSpawner`], like `Commands`, `World`,
+//!   `ChildSpawnerCommands` or `EntityCommands`. Entities spawned with `EntityCommands` become children of its entity.
+//!   ```rust
+//!   btml!(self.commands, <Node default />);
+//!   btml!(commands.entity(hud), <Text(new)>"Paused"</Text>);
+//!   btml!(world, <Camera2d />);
+//!   ```
+//!
+//! - **Immediate Mode**: `btml!(world => ...)` spawns directly into a `World`, for exclusive systems, tests and tools.
+//!   Nothing is queued, so the entities can be inspected as soon as the macro returns.
+//!   ```rust
+//!   let mut world = World::new();
+//!   let menu = btml!(world =>
+//!       <Node default>
+//!           <children>
+//!               <Text(new)>"Menu"</Text>
+//!           </children>
+//!       </Node>
+//!   );
+//!   assert_eq!(world.entity(menu).get::<Children>().unwrap().len(), 1);
+//!   ```
+//!   Note: Templates used in immediate mode have to take `&mut impl BtmlSpawner`.
+//!
+//! - **Appending Children**: `btml_children!` spawns markup as children of an existing entity, given as `EntityCommands`
+//!   or `EntityWorldMut`. No new root is created, the new children are its roots.
+//!   ```rust
+//!   let row: Entity = btml_children!(commands.entity(inventory),
+//!       <Node default>
+//!           <children>
+//!               <Text(new)>item.name.clone()</Text>
+//!           </children>
+//!       </Node>
+//!   );
+//!   ```
+//!   Note: Like with `btml!`, a single top-level node evaluates to its `Entity` (an `Option<Entity>` with a top-level
+//!   `let ... else`), and several nodes or a top-level loop to a `Vec<Entity>`. `btml_replace!` evaluates to the same.
+//!
+//! - **Replacing Children**: `btml_replace!` works like `btml_children!`, but despawns the existing children of the entity first.
+//!   Both happen in the same command batch, so the old and new children are never visible together.
+//!   ```rust
+//!   btml_replace!(commands.entity(list),
+//!       for entry in &log.entries {
+//!           <Text(new)>entry.clone()</Text>
+//!       }
+//!   );
+//!   ```
+//!
+//! - **Inserting Components**: `btml_insert!` inserts components on an existing entity, with the same syntax and defaults
+//!   as spawning. Its `<children>` and control flow are spawned as new children of the entity.
+//!   ```rust
+//!   btml_insert!(commands.entity(button),
+//!       <BackgroundColor>Color::srgb(0.3, 0.3, 0.6)</BackgroundColor>
+//!       <Node padding=UiRect::all(Val::Px(12.0)) />
+//!   );
+//!   ```
+//!   Note: Inserted components replace the ones already on the entity, so `<Node padding=... />` resets the other fields of `Node`.
+//!
+//! - **Patching**: `btml_patch!(commands, root, ...)` updates an existing tree to match the markup instead of spawning it again.
+//!   The markup gives the components of `root` and its children. Components are only inserted again when their value changed,
+//!   missing children are spawned and the children that aren't in the markup anymore are despawned.
+//!   ```rust
+//!   btml_patch!(commands, hud,
+//!       <Node default />
+//!       <children>
+//!           <Text(new)>format!("HP {}", hp)</Text>
+//!       </children>
+//!       <children>
+//!           for item in items {
+//!               <entity key=item.id>
+//!                   <Text(new)>item.name</Text>
+//!               </entity>
+//!           }
+//!       </children>
+//!   );
+//!   ```
+//!   Note: Children are matched in order, or by the `key` of their `<entity>` so they keep their identity when a list is reordered.
+//!   Patched components must implement `PartialEq`. With `Commands` the patch is queued, so the values it uses must be `Send + 'static`.
+//!
+//! - **Root Entity**: With a spawner, `btml!` evaluates to the `Entity` of the root, ready to be stored or parented.
+//!   ```rust
+//!   let menu = btml!(commands,
+//!       <Node default>
+//!           <children>
+//!               <Text(new)>"Menu"</Text>
+//!           </children>
+//!       </Node>
+//!   );
+//!   commands.insert_resource(MenuRoot(menu));
+//!   ```
+//!   Note: With a top-level `let ... else`, the root might not be spawned so it's an `Option<Entity>`.
+//!
+//! - **Multiple Roots**: Every top-level node tree is spawned as its own root, and so is every iteration of a top-level loop.
+//!   Then `btml!` evaluates to a `Vec<Entity>` of all the roots. Use `<entity>` to group top-level components into one entity.
+//!   ```rust
+//!   let roots: Vec<Entity> = btml!(commands,
+//!       <Camera2d />
+//!       <entity>
+//!           <PointLight default />
+//!           <Transform(from_xyz)>0.0, 8.0, 0.0</Transform>
+//!       </entity>
+//!   );
+//!   ```
+//!   Note: A top-level template adds the entities it returns (`Entity`, `Option<Entity>` or `Vec<Entity>`) to the roots.
+//!   Entities spawned by templates returning `()` and by Rust blocks at the top level aren't part of the roots.
+//!
+//! - **Capturing Entities**: Bind the id of a spawned entity to a variable with `#name`, to use it after the macro.
+//!   ```rust
+//!   btml!(commands,
+//!       <Node default, #menu>
+//!           <children>
+//!               <Button #play />
+//!           </children>
+//!       </Node>
+//!   );
+//!   commands.entity(menu).insert(Visibility::Hidden);
+//!   ```
+//!   Note: The variable is an `Entity`, an `Option<Entity>` inside `if`, `match` and `let ... else`, or a `Vec<Entity>` inside loops.
+//!   Since the variables are declared by the macro, it has to be used as a statement to capture entities.
+//!
+//! - **Observers**: `on:click=handler` adds an observer of the clicks on the spawned entity. All the `bevy_picking` pointer
+//!   events have an attribute, from `on:over` and `on:press` to `on:drag_start` and `on:scroll`. Other entity events are
+//!   observed with their type, like `on:Explode=handler`.
+//!   ```rust
+//!   btml!(commands,
+//!       <Button on:click=|_: On<Pointer<Click>>, mut score: ResMut<Score>| score.0 += 1 />
+//!   );
+//!   ```
+//!   Note: The handler is an observer system, so its parameters are typed like with `EntityCommands::observe`. Pointer events
+//!   need the `picking` feature, which is enabled by default. In `btml_patch!` the observers added by the markup are replaced
+//!   on every patch, so the handler of a reused child sees the values of the latest patch.
+//!
+//! - **Lifecycle Hooks**: `on_spawn` runs right after the entity is spawned, with its `EntityCommands`. `on_insert`, `on_remove`
+//!   and `on_despawn` observe the `Insert`, `Remove` and `Despawn` lifecycle events of the component of their node.
+//!   ```rust
+//!   btml!(commands,
+//!       <AudioPlayer(new)
+//!           on_spawn=|mut emitter: EntityCommands| { emitter.insert(Emitter::default()); },
+//!           on_despawn=|despawn: On<Despawn, AudioPlayer>, mut analytics: ResMut<Analytics>| analytics.log(despawn.entity)
+//!       >sound</AudioPlayer>
+//!   );
+//!   ```
+//!   Note: With a `World`, the commands of `on_spawn` are applied before the macro returns. The lifecycle observers are added
+//!   after the components, so they see the next inserts and not the first one. In `btml_patch!`, `on_spawn` only runs for new children.
+//!
+//! - **Bundles**: Without a spawner, `btml!` evaluates to a single bundle. The `<children>` are kept and spawned
+//!   with the bundle through `Children::spawn`, so markup can be returned from functions or used with `commands.spawn`.
+//!   ```rust
+//!   fn card(title: &str) -> impl Bundle {
+//!       btml!(
+//!           <Node default>
+//!               <children>
+//!                   <Text(new)>title</Text>
+//!               </children>
+//!           </Node>
+//!       )
+//!   }
+//!   commands.spawn(card("Play"));
+//!   ```
+//!   Control flow and Rust blocks are spawned as children through `SpawnWith`, with the values they use moved into it.
+//!   These values have to be `Send + Sync + 'static`.
+//!   ```rust
+//!   fn inventory(items: Vec<String>) -> impl Bundle {
+//!       btml!(
+//!           <Node default>
+//!               for item in items {
+//!                   <Text(new)>item</Text>
+//!               } else {
+//!                   <Text(new)>"Empty"</Text>
+//!               }
+//!           </Node>
+//!       )
+//!   }
+//!   ```
+//!   Note: A `let ... else` has to be inside a `<children>` block, since the components of a bundle can't be skipped.
+//!
+//! - **Templates**: Turn a function into a reusable piece of markup with `#[btml_template]`. The first
+//!   argument is the spawner, the other arguments become props. Invoke it with the function name as tag, so
+//!   `fn card` is used as `<card>`: template names are lowercase, since a tag starting with an uppercase letter is a component type.
+//!   ```rust
+//!   #[btml_template]
+//!   fn card(parent: &mut ChildSpawnerCommands, title: &str, color: Color) {
+//!       btml!(parent,
+//!           <Node padding=UiRect::all(Val::Px(10.0))>
+//!               <BackgroundColor>color</BackgroundColor>
+//!               <children>
+//!                   <Text(new)>title</Text>
+//!               </children>
+//!           </Node>
+//!       );
+//!   }
+//!
+//!   <children>
+//!       <card title="Play", color=Color::BLACK />
+//!   </children>
+//!   ```
+//!   Note: A template spawns its own entities, it is never merged with the components around it.
+//!
+//!   A template taking `&mut impl BtmlSpawner` can be used with any spawner, from a system or from an exclusive `World` access.
+//!   ```rust
+//!   #[btml_template]
+//!   fn label(parent: &mut impl BtmlSpawner, text: &str) -> Entity {
+//!       btml!(parent, <Text(new)>text</Text>)
+//!   }
+//!
+//!   let label: Vec<Entity> = btml!(commands, <label text="Score" />);
+//!   ```
+//!   A template returning the result of its `btml!` gives its entities to the roots of a top-level call.
+//!
+//! - **Slots**: A template declares slots with `#[btml_template(slots(...))]` and places them with `<slot name="..." />`.
+//!   The unnamed `<slot />` is the `default` slot. Markup inside `<slot>` in the template is used when the caller leaves the slot empty.
+//!   ```rust
+//!   #[btml_template(slots(header, default))]
+//!   fn panel(parent: &mut ChildSpawnerCommands) {
+//!       btml!(parent,
+//!           <Node flex_direction=FlexDirection::Column>
+//!               <children>
+//!                   <slot name="header">
+//!                       <Text(new)>"Untitled"</Text>
+//!                   </slot>
+//!               </children>
+//!               <children>
+//!                   <slot />
+//!               </children>
+//!           </Node>
+//!       );
+//!   }
+//!
+//!   <panel>
+//!       <slot name="header"><Text(new)>"Settings"</Text></slot>
+//!       <slot><card title="Play", color=Color::BLACK /></slot>
+//!   </panel>
+//!   ```
+//!   Markup passed to a template without any `<slot>` tag goes to the `default` slot.
+//!   Note: In a template taking `&mut impl BtmlSpawner`, slots have to be placed inside `<children>`.
+//!
+//! - **Props**: Template props are required unless marked `#[prop(default)]`, `#[prop(default = expr)]` or typed `Option<T>`.
+//!   A missing required prop or an unknown prop is a compile error on the tag.
+//!   ```rust
+//!   #[btml_template]
+//!   fn card(
+//!       parent: &mut ChildSpawnerCommands,
+//!       title: &str,
+//!       #[prop(default = Color::BLACK)] color: Color,
+//!       subtitle: Option<String>,
+//!   ) { /* ... */ }
+//!
+//!   <card title="Play" />
+//!   ```
+//!   Props can also be a struct deriving `BtmlProps`, taken by the template with `#[props]`.
+//!   ```rust
+//!   #[derive(BtmlProps)]
+//!   struct CardProps {
+//!       title: String,
+//!       #[prop(default)]
+//!       color: Color,
+//!   }
+//!
+//!   #[btml_template]
+//!   fn card(parent: &mut ChildSpawnerCommands, #[props] props: CardProps) { /* ... */ }
+//!   ```
 //!
 //!
 //! ### Example
@@ -88,21 +448,23 @@
 //! # Other examples
 //! You can find examples in the `examples/` folder of the repository.
 
-use proc_macro::TokenStream;
-use syn::parse_macro_input;
-
-mod codegen;
-mod parse;
+mod patch;
+mod spawner;
 
-use parse::BtmlInput;
+// The tests use the macros, whose code names this crate `bevy_btml`.
+#[cfg(test)]
+extern crate self as bevy_btml;
 
-#[proc_macro]
-pub fn btml(input: TokenStream) -> TokenStream {
-    let input_ast = parse_macro_input!(input as BtmlInput);
+pub use bevy_btml_macros::{
+    BtmlProps, btml, btml_children, btml_insert, btml_patch, btml_replace, btml_template,
+};
+pub use spawner::BtmlSpawner;
 
-    if let Some(spawner) = input_ast.spawner {
-        codegen::generate_spawn_code(&spawner, &input_ast.nodes).into()
-    } else {
-        codegen::generate_bundle_code(&input_ast.nodes).into()
-    }
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::patch::{BtmlPatchTarget, BtmlPatched, BtmlPatcher, patch_component, patch_key};
+    pub use crate::spawner::{AsBtmlSpawner, BtmlEntity, BtmlRoots, BtmlWorld, defer_btml};
+    pub use bevy_ecs;
+    #[cfg(feature = "picking")]
+    pub use bevy_picking;
 }
@@ -0,0 +1,411 @@
+use core::any::TypeId;
+use core::hash::{Hash, Hasher};
+use std::collections::hash_map::DefaultHasher;
+
+use bevy_ecs::{
+    bundle::Bundle,
+    component::Component,
+    entity::Entity,
+    event::EntityEvent,
+    hierarchy::{ChildOf, Children},
+    observer::ObservedBy,
+    system::{Commands, EntityCommands, IntoObserverSystem},
+    world::{EntityWorldMut, World},
+};
+
+use crate::BtmlSpawner;
+use crate::spawner::{BtmlObserver, btml_observer};
+
+/// Remembers which markup spawned a patched entity, so the next patch can find it again.
+#[derive(Component)]
+#[doc(hidden)]
+pub struct BtmlPatched {
+    shape: TypeId,
+    key: Option<u64>,
+}
+
+/// Walks the markup of `btml_patch!` against the children of an existing entity.
+///
+/// Children are matched by key, or else in order among the children spawned with the same
+/// components. The children that weren't matched are despawned when the patcher is dropped.
+#[doc(hidden)]
+pub struct BtmlPatcher<'w> {
+    world: &'w mut World,
+    parent: Entity,
+    unmatched: Vec<Entity>,
+    matched: Vec<Entity>,
+    spawned: Option<Entity>,
+}
+
+impl<'w> BtmlPatcher<'w> {
+    /// Returns `None` when `parent` doesn't exist anymore, like a root despawned before a queued
+    /// patch runs.
+    pub fn new(world: &'w mut World, parent: Entity) -> Option<Self> {
+        let unmatched = world
+            .get_entity(parent)
+            .ok()?
+            .get::<Children>()
+            .map(|children| children.to_vec())
+            .unwrap_or_default();
+
+        Some(BtmlPatcher {
+            world,
+            parent,
+            unmatched,
+            matched: Vec::new(),
+            spawned: None,
+        })
+    }
+
+    /// Patches the components of the entity whose children are patched.
+    pub fn patch_parent<B: Bundle>(
+        &mut self,
+        bundle: B,
+        patch: impl FnOnce(&mut EntityWorldMut, B),
+    ) {
+        if let Ok(mut parent) = self.world.get_entity_mut(self.parent) {
+            patch(&mut parent, bundle);
+        }
+    }
+
+    /// Patches the next child, or spawns it when no existing child matches.
+    pub fn patch<B: Bundle>(
+        &mut self,
+        key: Option<u64>,
+        bundle: B,
+        patch: impl FnOnce(&mut EntityWorldMut, B),
+    ) -> Entity {
+        let patched = BtmlPatched {
+            shape: TypeId::of::<B>(),
+            key,
+        };
+
+        let entity = match self.find::<B>(&patched) {
+            Some(entity) => {
+                self.remove_observers(entity);
+                let mut entity_mut = self.world.entity_mut(entity);
+                patch(&mut entity_mut, bundle);
+                if !entity_mut.contains::<BtmlPatched>() {
+                    entity_mut.insert(patched);
+                }
+                self.spawned = None;
+                entity
+            }
+            None => {
+                let entity = self
+                    .world
+                    .spawn((ChildOf(self.parent), patched, bundle))
+                    .id();
+                self.spawned = Some(entity);
+                entity
+            }
+        };
+
+        self.matched.push(entity);
+        entity
+    }
+
+    /// Patches the next child, then its own children.
+    pub fn patch_with_children<B: Bundle>(
+        &mut self,
+        key: Option<u64>,
+        bundle: B,
+        patch: impl FnOnce(&mut EntityWorldMut, B),
+        children: impl FnOnce(&mut BtmlPatcher<'_>),
+    ) -> Entity {
+        let entity = self.patch(key, bundle, patch);
+        if let Some(mut patcher) = BtmlPatcher::new(self.world, entity) {
+            children(&mut patcher);
+        }
+        entity
+    }
+
+    // The markup observes a patched entity again, so its handlers use the values of this patch.
+    fn remove_observers(&mut self, entity: Entity) {
+        let Some(observers) = self.world.get::<ObservedBy>(entity) else {
+            return;
+        };
+        let observers: Vec<Entity> = observers
+            .get()
+            .iter()
+            .copied()
+            .filter(|&observer| self.world.get::<BtmlObserver>(observer).is_some())
+            .collect();
+        for observer in observers {
+            self.world.despawn(observer);
+        }
+    }
+
+    // Children spawned by `btml!` aren't marked yet, so they match when they have the components.
+    fn find<B: Bundle>(&mut self, patched: &BtmlPatched) -> Option<Entity> {
+        let mut components = None;
+        let position = self.unmatched.iter().position(|&entity| {
+            match self.world.get::<BtmlPatched>(entity) {
+                Some(existing) => existing.shape == patched.shape && existing.key == patched.key,
+                None if patched.key.is_none() => {
+                    let components = components.get_or_insert_with(|| {
+                        self.world
+                            .register_bundle::<B>()
+                            .explicit_components()
+                            .to_vec()
+                    });
+                    let entity = self.world.entity(entity);
+                    components.iter().all(|&id| entity.contains_id(id))
+                }
+                None => false,
+            }
+        })?;
+
+        Some(self.unmatched.remove(position))
+    }
+}
+
+impl Drop for BtmlPatcher<'_> {
+    fn drop(&mut self) {
+        for entity in self.unmatched.drain(..) {
+            self.world.despawn(entity);
+        }
+
+        // Dropping never panics, since a panic while unwinding from the markup would abort.
+        let world = &*self.world;
+        self.matched
+            .retain(|&entity| world.get_entity(entity).is_ok());
+        let Ok(mut parent) = self.world.get_entity_mut(self.parent) else {
+            return;
+        };
+        let in_order = parent
+            .get::<Children>()
+            .is_some_and(|children| **children == *self.matched);
+        if !in_order {
+            parent.replace_children(&self.matched);
+        }
+    }
+}
+
+// Templates and Rust blocks spawn whole bundles, which are inserted again.
+impl BtmlSpawner for BtmlPatcher<'_> {
+    type ChildSpawner<'a> = BtmlPatcher<'a>;
+
+    fn spawn_btml(&mut self, bundle: impl Bundle) -> Entity {
+        self.patch(None, bundle, |entity, bundle| {
+            entity.insert(bundle);
+        })
+    }
+
+    fn spawn_btml_with_children(
+        &mut self,
+        bundle: impl Bundle,
+        children: impl FnOnce(&mut Self::ChildSpawner<'_>),
+    ) -> Entity {
+        self.patch_with_children(
+            None,
+            bundle,
+            |entity, bundle| {
+                entity.insert(bundle);
+            },
+            children,
+        )
+    }
+
+    // The observers of a patched entity were removed by `patch`, so every entity gets the handlers
+    // of the current markup.
+    fn observe_btml<E: EntityEvent, B: Bundle, M>(
+        &mut self,
+        entity: Entity,
+        observer: impl IntoObserverSystem<E, B, M>,
+    ) {
+        self.world.spawn(btml_observer(entity, observer));
+    }
+
+    // Only new entities run their spawn hooks.
+    fn on_spawn_btml(&mut self, entity: Entity, hook: impl FnOnce(EntityCommands)) {
+        if self.spawned == Some(entity) {
+            hook(self.world.commands().entity(entity));
+            self.world.flush();
+        }
+    }
+}
+
+/// Inserts a component again only when its value changed, to keep change detection accurate.
+#[doc(hidden)]
+pub fn patch_component<C: Component + PartialEq>(entity: &mut EntityWorldMut, value: C) {
+    if entity.get::<C>() != Some(&value) {
+        entity.insert(value);
+    }
+}
+
+#[doc(hidden)]
+pub fn patch_key(key: &impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Where `btml_patch!` runs: right away in a `World`, or in a command queued by `Commands`.
+#[doc(hidden)]
+#[diagnostic::on_unimplemented(
+    message = "`btml_patch!` patches through `Commands` or a `World`, found `{Self}`",
+    label = "expected `Commands` or a `World`"
+)]
+pub trait BtmlPatchTarget {
+    fn patch_btml(&mut self, patch: impl FnOnce(&mut World) + Send + 'static);
+}
+
+impl BtmlPatchTarget for Commands<'_, '_> {
+    fn patch_btml(&mut self, patch: impl FnOnce(&mut World) + Send + 'static) {
+        self.queue(patch);
+    }
+}
+
+impl BtmlPatchTarget for World {
+    fn patch_btml(&mut self, patch: impl FnOnce(&mut World) + Send + 'static) {
+        patch(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::prelude::*;
+
+    use crate::{btml, btml_patch};
+
+    #[derive(Component, Default, PartialEq)]
+    struct List;
+
+    #[derive(Component, PartialEq, Debug)]
+    struct Item(u32);
+
+    #[derive(Component, PartialEq)]
+    struct Banner;
+
+    #[derive(EntityEvent)]
+    struct Ping(Entity);
+
+    #[derive(Resource, Default)]
+    struct Pings(Vec<u32>);
+
+    fn children(world: &World, root: Entity) -> Vec<Entity> {
+        world
+            .get::<Children>(root)
+            .map(|children| children.to_vec())
+            .unwrap_or_default()
+    }
+
+    fn items(world: &World, root: Entity) -> Vec<u32> {
+        children(world, root)
+            .into_iter()
+            .filter_map(|child| world.get::<Item>(child).map(|item| item.0))
+            .collect()
+    }
+
+    fn patch_list(world: &mut World, root: Entity, ids: &[u32]) {
+        let ids = ids.to_vec();
+        btml_patch!(world, root,
+            <List>
+                for id in ids {
+                    <entity key=id>
+                        <Item>id</Item>
+                    </entity>
+                }
+            </List>
+        );
+    }
+
+    fn patch_banner(world: &mut World, root: Entity, show: bool) {
+        btml_patch!(world, root,
+            <List>
+                if show {
+                    <Banner />
+                }
+                <children>
+                    <Item>0</Item>
+                </children>
+            </List>
+        );
+    }
+
+    fn patch_handlers(world: &mut World, root: Entity, ids: &[u32]) {
+        let ids = ids.to_vec();
+        btml_patch!(world, root,
+            <List>
+                for id in ids {
+                    <Item on:Ping=move |_: On<Ping>, mut pings: ResMut<Pings>| pings.0.push(id)>
+                        id
+                    </Item>
+                }
+            </List>
+        );
+    }
+
+    #[test]
+    fn keyed_children_are_reordered() {
+        let mut world = World::new();
+        let root = btml!(world, <List />);
+
+        patch_list(&mut world, root, &[1, 2, 3]);
+        let before = children(&world, root);
+
+        patch_list(&mut world, root, &[3, 1, 2]);
+        assert_eq!(items(&world, root), [3, 1, 2]);
+        assert_eq!(children(&world, root), [before[2], before[0], before[1]]);
+    }
+
+    #[test]
+    fn removed_children_are_despawned() {
+        let mut world = World::new();
+        let root = btml!(world, <List />);
+
+        patch_list(&mut world, root, &[1, 2, 3]);
+        let before = children(&world, root);
+
+        patch_list(&mut world, root, &[2]);
+        assert_eq!(children(&world, root), [before[1]]);
+        assert!(world.get_entity(before[0]).is_err());
+        assert!(world.get_entity(before[2]).is_err());
+    }
+
+    #[test]
+    fn conditional_child_toggles() {
+        let mut world = World::new();
+        let root = btml!(world, <List />);
+
+        patch_banner(&mut world, root, false);
+        let item = children(&world, root);
+        assert_eq!(item.len(), 1);
+
+        patch_banner(&mut world, root, true);
+        let with_banner = children(&world, root);
+        assert_eq!(with_banner.len(), 2);
+        assert!(world.entity(with_banner[0]).contains::<Banner>());
+        assert_eq!(with_banner[1], item[0]);
+
+        patch_banner(&mut world, root, false);
+        assert_eq!(children(&world, root), item);
+        assert!(world.get_entity(with_banner[0]).is_err());
+    }
+
+    #[test]
+    fn despawned_root_is_skipped() {
+        let mut world = World::new();
+        let root = btml!(world, <List />);
+        world.despawn(root);
+
+        patch_list(&mut world, root, &[1, 2]);
+        assert_eq!(world.query::<&Item>().iter(&world).count(), 0);
+    }
+
+    #[test]
+    fn reused_children_get_current_handlers() {
+        let mut world = World::new();
+        world.init_resource::<Pings>();
+        let root = btml!(world, <List />);
+
+        patch_handlers(&mut world, root, &[1, 2]);
+        let first = children(&world, root)[0];
+
+        patch_handlers(&mut world, root, &[2]);
+        assert_eq!(children(&world, root), [first]);
+        world.trigger(Ping(first));
+        assert_eq!(world.resource::<Pings>().0, [2]);
+    }
+}
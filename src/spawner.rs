@@ -0,0 +1,381 @@
+use crate::patch::BtmlPatchTarget;
+use bevy_ecs::{
+    bundle::Bundle,
+    component::Component,
+    entity::Entity,
+    event::EntityEvent,
+    hierarchy::{ChildOf, ChildSpawner, ChildSpawnerCommands},
+    observer::Observer,
+    relationship::{RelatedSpawner, RelatedSpawnerCommands, Relationship},
+    system::{Commands, EntityCommands, IntoObserverSystem},
+    world::{EntityWorldMut, World},
+};
+
+/// Something `btml!` can spawn entities with.
+///
+/// It is implemented for [`Commands`], [`EntityCommands`] and [`EntityWorldMut`] (which spawn
+/// children of their entity), [`RelatedSpawnerCommands`], [`World`] and [`RelatedSpawner`].
+/// A template taking `&mut impl BtmlSpawner` can be used in all of these contexts.
+pub trait BtmlSpawner {
+    /// The spawner of the children of a spawned entity.
+    ///
+    /// The children of children are spawned with the same type, so markup nested at any depth
+    /// below a spawner has the same spawner type.
+    type ChildSpawner<'a>: for<'b> BtmlSpawner<ChildSpawner<'b> = Self::ChildSpawner<'b>>;
+
+    /// Spawns an entity and returns its id.
+    fn spawn_btml(&mut self, bundle: impl Bundle) -> Entity;
+
+    /// Spawns an entity with the children spawned by `children`, and returns its id.
+    fn spawn_btml_with_children(
+        &mut self,
+        bundle: impl Bundle,
+        children: impl FnOnce(&mut Self::ChildSpawner<'_>),
+    ) -> Entity;
+
+    /// Adds an observer of the events targeting `entity`, which was spawned by this spawner.
+    ///
+    /// The observer is marked as added by the markup, so `btml_patch!` can replace it with the
+    /// handler of the current patch when it reuses `entity`.
+    fn observe_btml<E: EntityEvent, B: Bundle, M>(
+        &mut self,
+        entity: Entity,
+        observer: impl IntoObserverSystem<E, B, M>,
+    );
+
+    /// Runs `hook` with the commands of `entity`, which was spawned by this spawner.
+    ///
+    /// Spawners writing directly into a `World` apply the commands right away.
+    fn on_spawn_btml(&mut self, entity: Entity, hook: impl FnOnce(EntityCommands));
+
+    /// Lets `btml!` borrow any spawner expression, whether it's a value or a reference.
+    #[doc(hidden)]
+    fn as_btml_spawner(&mut self) -> &mut Self {
+        self
+    }
+
+    /// Gives the type of its spawner to a closure spawning children before their parent exists.
+    #[doc(hidden)]
+    fn defer_btml_children<C, F>(&self, children: F) -> F
+    where
+        F: FnOnce(&mut Self::ChildSpawner<'_>, C) -> C,
+    {
+        children
+    }
+}
+
+impl BtmlSpawner for Commands<'_, '_> {
+    type ChildSpawner<'a> = ChildSpawnerCommands<'a>;
+
+    fn spawn_btml(&mut self, bundle: impl Bundle) -> Entity {
+        self.spawn(bundle).id()
+    }
+
+    fn spawn_btml_with_children(
+        &mut self,
+        bundle: impl Bundle,
+        children: impl FnOnce(&mut Self::ChildSpawner<'_>),
+    ) -> Entity {
+        self.spawn(bundle).with_children(children).id()
+    }
+
+    fn observe_btml<E: EntityEvent, B: Bundle, M>(
+        &mut self,
+        entity: Entity,
+        observer: impl IntoObserverSystem<E, B, M>,
+    ) {
+        self.spawn(btml_observer(entity, observer));
+    }
+
+    fn on_spawn_btml(&mut self, entity: Entity, hook: impl FnOnce(EntityCommands)) {
+        hook(self.entity(entity));
+    }
+}
+
+impl BtmlSpawner for EntityCommands<'_> {
+    type ChildSpawner<'a> = ChildSpawnerCommands<'a>;
+
+    fn spawn_btml(&mut self, bundle: impl Bundle) -> Entity {
+        let parent = self.id();
+        self.commands().spawn((ChildOf(parent), bundle)).id()
+    }
+
+    fn spawn_btml_with_children(
+        &mut self,
+        bundle: impl Bundle,
+        children: impl FnOnce(&mut Self::ChildSpawner<'_>),
+    ) -> Entity {
+        let parent = self.id();
+        self.commands()
+            .spawn((ChildOf(parent), bundle))
+            .with_children(children)
+            .id()
+    }
+
+    fn observe_btml<E: EntityEvent, B: Bundle, M>(
+        &mut self,
+        entity: Entity,
+        observer: impl IntoObserverSystem<E, B, M>,
+    ) {
+        self.commands().spawn(btml_observer(entity, observer));
+    }
+
+    fn on_spawn_btml(&mut self, entity: Entity, hook: impl FnOnce(EntityCommands)) {
+        hook(self.commands().entity(entity));
+    }
+}
+
+impl<R: Relationship> BtmlSpawner for RelatedSpawnerCommands<'_, R> {
+    type ChildSpawner<'a> = ChildSpawnerCommands<'a>;
+
+    fn spawn_btml(&mut self, bundle: impl Bundle) -> Entity {
+        self.spawn(bundle).id()
+    }
+
+    fn spawn_btml_with_children(
+        &mut self,
+        bundle: impl Bundle,
+        children: impl FnOnce(&mut Self::ChildSpawner<'_>),
+    ) -> Entity {
+        self.spawn(bundle).with_children(children).id()
+    }
+
+    fn observe_btml<E: EntityEvent, B: Bundle, M>(
+        &mut self,
+        entity: Entity,
+        observer: impl IntoObserverSystem<E, B, M>,
+    ) {
+        self.commands().spawn(btml_observer(entity, observer));
+    }
+
+    fn on_spawn_btml(&mut self, entity: Entity, hook: impl FnOnce(EntityCommands)) {
+        hook(self.commands().entity(entity));
+    }
+}
+
+impl BtmlSpawner for World {
+    type ChildSpawner<'a> = ChildSpawner<'a>;
+
+    fn spawn_btml(&mut self, bundle: impl Bundle) -> Entity {
+        self.spawn(bundle).id()
+    }
+
+    fn spawn_btml_with_children(
+        &mut self,
+        bundle: impl Bundle,
+        children: impl FnOnce(&mut Self::ChildSpawner<'_>),
+    ) -> Entity {
+        self.spawn(bundle).with_children(children).id()
+    }
+
+    fn observe_btml<E: EntityEvent, B: Bundle, M>(
+        &mut self,
+        entity: Entity,
+        observer: impl IntoObserverSystem<E, B, M>,
+    ) {
+        self.spawn(btml_observer(entity, observer));
+    }
+
+    fn on_spawn_btml(&mut self, entity: Entity, hook: impl FnOnce(EntityCommands)) {
+        hook(self.commands().entity(entity));
+        self.flush();
+    }
+}
+
+impl BtmlSpawner for EntityWorldMut<'_> {
+    type ChildSpawner<'a> = ChildSpawner<'a>;
+
+    fn spawn_btml(&mut self, bundle: impl Bundle) -> Entity {
+        let mut entity = Entity::PLACEHOLDER;
+        self.with_children(|parent| entity = parent.spawn(bundle).id());
+        entity
+    }
+
+    fn spawn_btml_with_children(
+        &mut self,
+        bundle: impl Bundle,
+        children: impl FnOnce(&mut Self::ChildSpawner<'_>),
+    ) -> Entity {
+        let mut entity = Entity::PLACEHOLDER;
+        self.with_children(|parent| {
+            entity = parent.spawn(bundle).with_children(children).id();
+        });
+        entity
+    }
+
+    fn observe_btml<E: EntityEvent, B: Bundle, M>(
+        &mut self,
+        entity: Entity,
+        observer: impl IntoObserverSystem<E, B, M>,
+    ) {
+        self.world_scope(|world| {
+            world.spawn(btml_observer(entity, observer));
+        });
+    }
+
+    fn on_spawn_btml(&mut self, entity: Entity, hook: impl FnOnce(EntityCommands)) {
+        self.world_scope(|world| {
+            hook(world.commands().entity(entity));
+            world.flush();
+        });
+    }
+}
+
+impl<R: Relationship> BtmlSpawner for RelatedSpawner<'_, R> {
+    type ChildSpawner<'a> = ChildSpawner<'a>;
+
+    fn spawn_btml(&mut self, bundle: impl Bundle) -> Entity {
+        self.spawn(bundle).id()
+    }
+
+    fn spawn_btml_with_children(
+        &mut self,
+        bundle: impl Bundle,
+        children: impl FnOnce(&mut Self::ChildSpawner<'_>),
+    ) -> Entity {
+        self.spawn(bundle).with_children(children).id()
+    }
+
+    fn observe_btml<E: EntityEvent, B: Bundle, M>(
+        &mut self,
+        entity: Entity,
+        observer: impl IntoObserverSystem<E, B, M>,
+    ) {
+        self.world_mut().spawn(btml_observer(entity, observer));
+    }
+
+    fn on_spawn_btml(&mut self, entity: Entity, hook: impl FnOnce(EntityCommands)) {
+        let world = self.world_mut();
+        hook(world.commands().entity(entity));
+        world.flush();
+    }
+}
+
+/// The spawners of `btml!(world => ...)`, which only accepts a `World`.
+#[doc(hidden)]
+#[diagnostic::on_unimplemented(
+    message = "`btml!(spawner => ...)` spawns directly into a `World`, found `{Self}`",
+    label = "expected a `World`",
+    note = "use `btml!(spawner, ...)` for other spawners"
+)]
+pub trait BtmlWorld {
+    fn world(&mut self) -> &mut World;
+}
+
+impl BtmlWorld for World {
+    fn world(&mut self) -> &mut World {
+        self
+    }
+}
+
+/// The existing entities of `btml_children!`, `btml_insert!` and `btml_replace!`, which spawn
+/// children of the entity, insert components on it and despawn its children.
+#[doc(hidden)]
+#[diagnostic::on_unimplemented(
+    message = "expected an existing entity, found `{Self}`",
+    label = "expected `EntityCommands` or `EntityWorldMut`",
+    note = "`btml_children!`, `btml_insert!` and `btml_replace!` take `commands.entity(e)` or `world.entity_mut(e)`"
+)]
+pub trait BtmlEntity: BtmlSpawner {
+    fn insert_btml(&mut self, bundle: impl Bundle);
+
+    fn despawn_btml_children(&mut self);
+}
+
+impl BtmlEntity for EntityCommands<'_> {
+    fn insert_btml(&mut self, bundle: impl Bundle) {
+        self.insert(bundle);
+    }
+
+    fn despawn_btml_children(&mut self) {
+        self.despawn_children();
+    }
+}
+
+impl BtmlEntity for EntityWorldMut<'_> {
+    fn insert_btml(&mut self, bundle: impl Bundle) {
+        self.insert(bundle);
+    }
+
+    fn despawn_btml_children(&mut self) {
+        self.despawn_children();
+    }
+}
+
+/// Marks the observers added by the markup, which `btml_patch!` replaces.
+#[derive(Component)]
+pub(crate) struct BtmlObserver;
+
+pub(crate) fn btml_observer<E: EntityEvent, B: Bundle, M>(
+    entity: Entity,
+    observer: impl IntoObserverSystem<E, B, M>,
+) -> impl Bundle {
+    (Observer::new(observer).with_entity(entity), BtmlObserver)
+}
+
+/// Gives the type of `spawner` to a closure spawning more of its children later.
+#[doc(hidden)]
+pub fn defer_btml<S: ?Sized, C, F: FnOnce(&mut S, C) -> C>(_spawner: &S, children: F) -> F {
+    children
+}
+
+/// Borrows any spawner expression, so a wrong one is reported through [`BtmlWorld`],
+/// [`BtmlEntity`] or [`BtmlPatchTarget`].
+#[doc(hidden)]
+pub trait AsBtmlSpawner {
+    fn as_btml_world(&mut self) -> &mut World
+    where
+        Self: BtmlWorld,
+    {
+        self.world()
+    }
+
+    fn as_btml_entity(&mut self) -> &mut Self
+    where
+        Self: BtmlEntity,
+    {
+        self
+    }
+
+    fn as_btml_patch_target(&mut self) -> &mut Self
+    where
+        Self: BtmlPatchTarget,
+    {
+        self
+    }
+}
+
+impl<T: ?Sized> AsBtmlSpawner for T {}
+
+/// The entities returned by a template, which become roots when it's used at the top level.
+#[doc(hidden)]
+#[diagnostic::on_unimplemented(
+    message = "a template returns `()`, `Entity`, `Option<Entity>` or `Vec<Entity>`, found `{Self}`"
+)]
+pub trait BtmlRoots {
+    fn into_btml_roots(self) -> Vec<Entity>;
+}
+
+impl BtmlRoots for () {
+    fn into_btml_roots(self) -> Vec<Entity> {
+        Vec::new()
+    }
+}
+
+impl BtmlRoots for Entity {
+    fn into_btml_roots(self) -> Vec<Entity> {
+        vec![self]
+    }
+}
+
+impl BtmlRoots for Option<Entity> {
+    fn into_btml_roots(self) -> Vec<Entity> {
+        self.into_iter().collect()
+    }
+}
+
+impl BtmlRoots for Vec<Entity> {
+    fn into_btml_roots(self) -> Vec<Entity> {
+        self
+    }
+}
@@ -0,0 +1,114 @@
+use bevy_btml::btml;
+use bevy_ecs::prelude::*;
+
+#[derive(Component)]
+struct Panel;
+
+#[derive(Component)]
+struct Value(u32);
+
+#[derive(Component)]
+struct Item;
+
+#[derive(Component)]
+struct Fallback;
+
+fn child_values(world: &World, parent: Entity) -> Vec<Option<u32>> {
+    world
+        .entity(parent)
+        .get::<Children>()
+        .map(|children| {
+            children
+                .iter()
+                .map(|child| world.entity(child).get::<Value>().map(|value| value.0))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[test]
+fn bindings_shadow_in_source_order() {
+    let mut world = World::new();
+
+    let panel = btml!(world,
+        <Panel>
+            let x = 1;
+            <children>
+                <Value>x</Value>
+            </children>
+            let x = x + 1;
+            <children>
+                <Value>x</Value>
+            </children>
+        </Panel>
+    );
+    assert_eq!(child_values(&world, panel), [Some(1), Some(2)]);
+
+    let value = btml!(world,
+        <entity>
+            let x = 1;
+            <Value>x</Value>
+            let x = "shadowed";
+            <Name(new)>x</Name>
+        </entity>
+    );
+    assert_eq!(world.entity(value).get::<Value>().unwrap().0, 1);
+    assert_eq!(
+        world.entity(value).get::<Name>().unwrap().as_str(),
+        "shadowed"
+    );
+}
+
+#[test]
+fn let_else_keeps_earlier_siblings() {
+    let mut world = World::new();
+
+    let panel = btml!(world,
+        <Panel>
+            <children>
+                <Item />
+            </children>
+            let Some(v) = None::<u32> else {
+                <children>
+                    <Fallback />
+                </children>
+            };
+            <children>
+                <Value>v</Value>
+            </children>
+        </Panel>
+    );
+    let children = world.entity(panel).get::<Children>().unwrap().to_vec();
+    assert_eq!(children.len(), 2);
+    assert!(world.entity(children[0]).contains::<Item>());
+    assert!(world.entity(children[1]).contains::<Fallback>());
+
+    let roots = btml!(world,
+        <Item />
+        let Some(v) = None::<u32> else {
+            <Fallback />
+        };
+        <Value>v</Value>
+    );
+    assert_eq!(roots.len(), 2);
+    assert!(world.entity(roots[0]).contains::<Item>());
+    assert!(world.entity(roots[1]).contains::<Fallback>());
+}
+
+#[test]
+fn capture_after_binding() {
+    let mut world = World::new();
+
+    btml!(world,
+        <Panel>
+            for _ in 0..1 {
+                <Item #items />
+            }
+            let count = 2;
+            for _ in 0..count {
+                <Item #items />
+            }
+        </Panel>
+    );
+    assert_eq!(items.len(), 3);
+}
@@ -0,0 +1,100 @@
+use bevy_btml::{BtmlSpawner, btml, btml_template};
+use bevy_ecs::prelude::*;
+
+#[derive(Component, Default)]
+struct Panel;
+
+#[derive(Component)]
+struct Item;
+
+#[derive(Component)]
+struct FallbackSpawned(bool);
+
+#[btml_template(slots(default))]
+fn maybe(parent: &mut impl BtmlSpawner, show: bool) {
+    btml!(parent,
+        <Panel>
+            <children>
+                if show {
+                    <slot />
+                }
+            </children>
+        </Panel>
+    );
+}
+
+#[btml_template(slots(default))]
+fn with_fallback(parent: &mut impl BtmlSpawner) {
+    btml!(parent,
+        <Panel>
+            <children>
+                <slot>
+                    <Item #fallback />
+                </slot>
+            </children>
+        </Panel>
+    );
+    btml!(parent, <FallbackSpawned>fallback.is_some()</FallbackSpawned>);
+}
+
+#[test]
+fn capture_in_skipped_slot_content() {
+    let mut world = World::new();
+
+    btml!(world,
+        <maybe show=false>
+            <Item #inner />
+        </maybe>
+    );
+    assert_eq!(inner, None);
+
+    btml!(world,
+        <maybe show=true>
+            <Item #inner />
+        </maybe>
+    );
+    assert!(world.entity(inner.unwrap()).contains::<Item>());
+}
+
+#[test]
+fn capture_in_unused_slot_fallback() {
+    let mut world = World::new();
+
+    btml!(world, <with_fallback />);
+    btml!(world,
+        <with_fallback>
+            <Panel />
+        </with_fallback>
+    );
+
+    let mut spawned: Vec<bool> = world
+        .query::<&FallbackSpawned>()
+        .iter(&world)
+        .map(|spawned| spawned.0)
+        .collect();
+    spawned.sort();
+    assert_eq!(spawned, [false, true]);
+}
+
+#[test]
+fn capture_on_entity_without_components() {
+    let mut world = World::new();
+
+    btml!(world,
+        <Panel #panel>
+            <children>
+                <entity #wrapper>
+                    <children>
+                        <Item #item />
+                    </children>
+                </entity>
+            </children>
+        </Panel>
+    );
+
+    assert_eq!(world.entity(item).get::<ChildOf>(), Some(&ChildOf(wrapper)));
+    assert_eq!(
+        world.entity(wrapper).get::<ChildOf>(),
+        Some(&ChildOf(panel))
+    );
+}
@@ -0,0 +1,39 @@
+use bevy_btml::btml;
+use bevy_ecs::prelude::*;
+
+#[derive(Component)]
+struct Label(&'static str);
+
+#[test]
+fn match_expression_as_content() {
+    let mut world = World::new();
+
+    let state = 1;
+    let label = btml!(world,
+        <Label>match state { 1 => "one", _ => "other" }</Label>
+    );
+    assert_eq!(world.entity(label).get::<Label>().unwrap().0, "one");
+
+    let label = btml!(world,
+        <Label>
+            match state {
+                0 => { "zero" }
+                _ => "other"
+            }
+        </Label>
+    );
+    assert_eq!(world.entity(label).get::<Label>().unwrap().0, "other");
+
+    let root = btml!(world,
+        <entity>
+            match state {
+                1 => {
+                    <Label>"markup"</Label>
+                }
+                _ => {}
+            }
+        </entity>
+    );
+    let child = world.entity(root).get::<Children>().unwrap()[0];
+    assert_eq!(world.entity(child).get::<Label>().unwrap().0, "markup");
+}
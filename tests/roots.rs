@@ -0,0 +1,40 @@
+use bevy_btml::{BtmlSpawner, btml, btml_template};
+use bevy_ecs::prelude::*;
+
+#[derive(Component)]
+struct Card(u32);
+
+#[derive(Component)]
+struct Marker;
+
+#[btml_template]
+fn card(parent: &mut impl BtmlSpawner, id: u32) -> Entity {
+    btml!(parent, <Card>id</Card>)
+}
+
+#[btml_template]
+fn marker(parent: &mut impl BtmlSpawner) {
+    btml!(parent, <Marker />);
+}
+
+#[test]
+fn template_roots() {
+    let mut world = World::new();
+
+    let roots = btml!(world, <card id=1 />);
+    assert_eq!(roots.len(), 1);
+    assert_eq!(world.entity(roots[0]).get::<Card>().unwrap().0, 1);
+
+    let roots = btml!(world,
+        <Marker />
+        for id in 2..4 {
+            <card id=id />
+        }
+        <marker />
+    );
+    let ids: Vec<Option<u32>> = roots
+        .iter()
+        .map(|root| world.entity(*root).get::<Card>().map(|card| card.0))
+        .collect();
+    assert_eq!(ids, [None, Some(2), Some(3)]);
+}